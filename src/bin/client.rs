@@ -1,8 +1,10 @@
-use std::{num::ParseIntError, str, time::Duration};
+use std::{num::ParseIntError, path::PathBuf, str, time::Duration};
 
 use bytes::Bytes;
-use clap::{Parser, command};
-use my_redis::client;
+use clap::Parser;
+use my_redis::{DEFAULT_PORT, client, tls};
+use tokio::{net::lookup_host, signal};
+use tokio_stream::StreamExt;
 
 #[derive(Parser, Debug)]
 #[command(name = "my-redis-cli", version = env!("CARGO_PKG_VERSION"), author = env!("CARGO_PKG_AUTHORS"), about = "Issue Redis commands")]
@@ -18,8 +20,24 @@ struct Cli {
     addr: String,
 
     /// Redis 服务器的端口号。
-    #[arg(short, long, default_value="6379",help = "Redis port",required=false)]
+    #[arg(short, long, default_value=DEFAULT_PORT,help = "Redis port",required=false)]
     port: String,
+
+    /// 使用 TLS 连接服务器
+    #[arg(long)]
+    tls: bool,
+
+    /// 用于校验服务器证书的 CA 证书文件（PEM）
+    #[arg(long, requires = "tls")]
+    tls_ca: Option<PathBuf>,
+
+    /// 不校验服务器证书，只应在测试环境中使用
+    #[arg(long, requires = "tls")]
+    tls_insecure: bool,
+
+    /// 服务器配置了密码时用于认证的密码
+    #[arg(long)]
+    password: Option<String>,
 }
 
 #[derive(Debug, Parser)]
@@ -34,6 +52,17 @@ enum Command {
         #[arg(short,long,value_parser=duration_from_str)]
         expires: Option<Duration>,
     },
+    /// 向频道发布一条消息
+    Publish {
+        channel: String,
+        #[arg(value_parser=bytes_from_str)]
+        message: Bytes,
+    },
+    /// 订阅一个或多个频道，持续打印收到的消息直到按下 Ctrl-C
+    Subscribe {
+        #[arg(required = true)]
+        channels: Vec<String>,
+    },
 }
 
 fn duration_from_str(s: &str) -> Result<Duration, ParseIntError> {
@@ -53,7 +82,22 @@ async fn main() -> my_redis::Result<()> {
 
     let addr = format!("{}:{}", cli.addr, cli.port);
 
-    let mut client = client::connect(&addr).await?;
+    // 连接之前先解析地址，解析失败时给出明确的错误信息
+    let addr = match lookup_host(&addr).await.map(|mut addrs| addrs.next()) {
+        Ok(Some(addr)) => addr,
+        Ok(None) => return Err(format!("address `{}` did not resolve to any host", addr).into()),
+        Err(err) => return Err(format!("invalid address `{}`: {}", addr, err).into()),
+    };
+
+    let mut client = if cli.tls {
+        let connector = tls::connector(cli.tls_ca.as_deref(), cli.tls_insecure)?;
+        client::connect_tls(addr, &cli.addr, connector).await?
+    } else {
+        client::connect(addr).await?
+    };
+    if let Some(password) = &cli.password {
+        client.auth(password).await?;
+    }
 
     match cli.command {
         Command::Get { key } => {
@@ -83,6 +127,32 @@ async fn main() -> my_redis::Result<()> {
             client.set_expires(&key, value, expires).await?;
             println!("OK");
         }
+        Command::Publish { channel, message } => {
+            let receivers = client.publish(&channel, message).await?;
+            println!(
+                "Publish OK, {} subscriber(s) received the message",
+                receivers
+            );
+        }
+        Command::Subscribe { channels } => {
+            let mut messages = Box::pin(client.subscribe(channels).await?.into_stream());
+            loop {
+                tokio::select! {
+                    message = messages.next() => match message {
+                        Some(message) => {
+                            let message = message?;
+                            match str::from_utf8(&message.content) {
+                                Ok(content) => println!("{}: {}", message.channel, content),
+                                Err(_) => println!("{}: {:?}", message.channel, message.content),
+                            }
+                        }
+                        // 服务器关闭了连接
+                        None => break,
+                    },
+                    _ = signal::ctrl_c() => break,
+                }
+            }
+        }
     }
 
     Ok(())
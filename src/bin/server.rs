@@ -1,5 +1,13 @@
+use std::{fmt::Display, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
+
 use clap::Parser;
-use my_redis::{DEFAULT_PORT, server};
+use my_redis::{
+    DEFAULT_PORT,
+    server::{self, ServerListener},
+    tls,
+};
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::{net::TcpListener, signal};
 
 #[tokio::main]
@@ -7,17 +15,120 @@ pub async fn main() -> my_redis::Result<()> {
     tracing_subscriber::fmt::try_init()?;
 
     let cli = Cli::parse();
-    let port = cli.port.as_deref().unwrap_or(DEFAULT_PORT);
+    let listener = bind(&cli).await?;
 
-    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
+    let config = server::Config {
+        max_connections: cli.max_connections,
+        idle_timeout: cli.idle_timeout.map(Duration::from_secs),
+        channel_capacity: cli.channel_capacity,
+        read_buffer: cli.read_buffer,
+        max_frame_size: cli.max_frame_size,
+        databases: cli.databases,
+        requirepass: cli.requirepass,
+        set_max_listpack_entries: cli.set_max_listpack_entries,
+        set_max_listpack_value: cli.set_max_listpack_value,
+    };
 
-    server::run(listener, signal::ctrl_c()).await?;
+    server::run(listener, config, signal::ctrl_c()).await?;
     Ok(())
 }
 
+/// 指定了 `--unix-socket` 时监听 UNIX 套接字，否则监听 TCP 地址
+async fn bind(cli: &Cli) -> my_redis::Result<ServerListener> {
+    #[cfg(unix)]
+    if let Some(path) = &cli.unix_socket {
+        // 与 Redis 一致，先删除上次运行遗留的套接字文件
+        let _ = std::fs::remove_file(path);
+        let listener = UnixListener::bind(path)
+            .map_err(|err| format!("failed to bind {}: {}", path.display(), err))?;
+        return Ok(listener.into());
+    }
+
+    let port = cli.port.as_deref().unwrap_or(DEFAULT_PORT);
+    let addr = format!("{}:{}", cli.host, port);
+    let addr: SocketAddr = addr
+        .parse()
+        .map_err(|_| format!("invalid listen address `{}`", addr))?;
+
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|err| format!("failed to bind {}: {}", addr, err))?;
+
+    match (&cli.tls_cert, &cli.tls_key) {
+        (Some(cert), Some(key)) => Ok(ServerListener::Tls(listener, tls::acceptor(cert, key)?)),
+        (None, None) => Ok(listener.into()),
+        _ => Err("--tls-cert and --tls-key must be given together".into()),
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(name="my-redis-server",version=env!("CARGO_PKG_VERSION"),author=env!("CARGO_PKG_AUTHORS"),about="A Redis server")]
 struct Cli {
+    /// 监听的地址，例如 `0.0.0.0` 表示所有网卡
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
     #[arg(long, short)]
     port: Option<String>,
+
+    /// 监听的 UNIX 套接字路径，设置后不再监听 TCP 地址
+    #[arg(long)]
+    unix_socket: Option<PathBuf>,
+
+    /// TLS 证书链文件（PEM），与 `--tls-key` 一起设置后只接受 TLS 连接
+    #[arg(long)]
+    tls_cert: Option<PathBuf>,
+
+    /// TLS 私钥文件（PEM）
+    #[arg(long)]
+    tls_key: Option<PathBuf>,
+
+    /// 最大并发连接数，必须大于 0
+    #[arg(long, default_value_t = server::DEFAULT_MAX_CONNECTIONS, value_parser = non_zero::<usize>)]
+    max_connections: usize,
+
+    /// 连接空闲多少秒后关闭，必须大于 0，不设置时不限制
+    #[arg(long, value_parser = non_zero::<u64>)]
+    idle_timeout: Option<u64>,
+
+    /// 每个发布订阅频道可缓存的消息数，必须大于 0
+    #[arg(long, default_value_t = server::DEFAULT_CHANNEL_CAPACITY, value_parser = non_zero::<usize>)]
+    channel_capacity: usize,
+
+    /// 每个连接读缓冲区的初始字节数，传输较大的值时可以调大
+    #[arg(long, default_value_t = server::DEFAULT_READ_BUFFER)]
+    read_buffer: usize,
+
+    /// 客户端发送的单个字符串或数组允许声明的最大长度
+    #[arg(long, default_value_t = server::DEFAULT_MAX_FRAME_SIZE)]
+    max_frame_size: usize,
+
+    /// 逻辑数据库的数量
+    #[arg(long, default_value_t = server::DEFAULT_DATABASES)]
+    databases: usize,
+
+    /// 客户端需要通过 `AUTH` 提供的密码
+    #[arg(long)]
+    requirepass: Option<String>,
+
+    /// 集合使用 listpack 编码的最大成员数量
+    #[arg(long, default_value_t = server::DEFAULT_SET_MAX_LISTPACK_ENTRIES)]
+    set_max_listpack_entries: usize,
+
+    /// 集合使用 listpack 编码时单个成员的最大字节数
+    #[arg(long, default_value_t = server::DEFAULT_SET_MAX_LISTPACK_VALUE)]
+    set_max_listpack_value: usize,
+}
+
+/// 解析必须大于 0 的数值参数
+fn non_zero<T>(s: &str) -> Result<T, String>
+where
+    T: FromStr + Default + PartialEq,
+    T::Err: Display,
+{
+    let value = s.parse::<T>().map_err(|err| err.to_string())?;
+    if value == T::default() {
+        return Err("must be greater than 0".to_string());
+    }
+    Ok(value)
 }
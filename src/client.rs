@@ -5,43 +5,158 @@ use std::{
 
 use async_stream::try_stream;
 use bytes::Bytes;
-use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::{
+    net::{TcpStream, ToSocketAddrs},
+    time,
+};
+use tokio_rustls::{TlsConnector, rustls::pki_types::ServerName};
 use tokio_stream::Stream;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::{
-    cmd::{Get, Publish, Set, Subscribe, Unsubscribe},
-    connection::Connection,
+    cmd::{
+        Acl, Append, Auth, ClientNoEvict, ClientNoTouch, CommandCmd, DbSize, Decr, Del, Echo,
+        Exists, Expire, FlushDb, Get, GetDel, GetRange, GetSet, HDel, HGet, HGetAll, HSet, Hello,
+        Incr, IncrByFloat, Info, Keys, LAGGED_PREFIX, LIndex, LLen, LPop, LPush, LRange, MGet,
+        MSet, MSetNx, Move, PSetEx, PSubscribe, PUnsubscribe, Persist, Ping, PubSub, Publish, Quit,
+        RPop, RPush, Rename, RenameNx, Reset, SAdd, SIsMember, SMembers, SRem, Scan, Select, Set,
+        SetEx, SetNx, Strlen, Subscribe, SwapDb, Ttl, Type, Unsubscribe,
+    },
+    connection::{Connection, ConnectionReset},
     frame::Frame,
 };
 
 pub struct Client {
     connection: Connection,
+    // 每次写入请求和读取回复的超时时间，`None` 表示不限制
+    timeout: Option<Duration>,
+    // 已超时但回复尚未读取的请求数量，这些迟到的回复会在下次读取时被丢弃
+    pending: usize,
+    // 连接出现读写错误或已被服务器关闭，不能再继续使用
+    broken: bool,
+}
+
+/// 流水线请求，缓存多条命令后一次性发送，再按顺序读取同样数量的回复
+pub struct Pipeline<'a> {
+    client: &'a mut Client,
+    frames: Vec<Frame>,
+}
+
+/// 断线重连的重试策略
+/// 重新连接时等待时间从 `initial_backoff` 开始每次翻倍，不超过 `max_backoff`，最多尝试 `max_retries` 次
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+/// 连接断开后自动重连的客户端，幂等的读命令在重连后会重试
+pub struct ReconnectingClient {
+    addr: String,
+    policy: RetryPolicy,
+    client: Option<Client>,
+}
+
+/// 连接断开后自动重连并重新订阅原有频道和模式的订阅者
+pub struct ReconnectingSubscriber {
+    addr: String,
+    policy: RetryPolicy,
+    subscriber: Subscriber,
 }
 
 pub struct Subscriber {
     client: Client,
     subscribed_channels: Vec<String>,
+    subscribed_patterns: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Message {
     pub channel: String,
     pub content: Bytes,
+    /// 通过模式订阅收到的消息所匹配的模式，直接订阅频道时为 `None`
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AclLogEntry {
+    pub reason: String,
+    pub object: String,
+    pub username: String,
+    pub age_seconds: f64,
+    pub client_info: String,
+}
+
+/// 建立 TLS 连接，`domain` 用于 SNI 和校验服务器证书
+pub async fn connect_tls<T: ToSocketAddrs>(
+    addr: T,
+    domain: &str,
+    connector: TlsConnector,
+) -> crate::Result<Client> {
+    let domain = ServerName::try_from(domain.to_string())?;
+    let socket = TcpStream::connect(addr).await?;
+    let socket = connector.connect(domain, socket).await?;
+    Ok(Client {
+        connection: Connection::new(Box::new(socket)),
+        timeout: None,
+        pending: 0,
+        broken: false,
+    })
 }
 
 pub async fn connect<T: ToSocketAddrs>(addr: T) -> crate::Result<Client> {
     let socket = TcpStream::connect(addr).await?;
-    let connection = Connection::new(socket);
-    Ok(Client { connection })
+    Ok(Client {
+        connection: Connection::new(Box::new(socket)),
+        timeout: None,
+        pending: 0,
+        broken: false,
+    })
 }
 
 impl Client {
+    /// 设置每次写入请求和读取回复的超时时间，超时后命令返回 `ErrorKind::TimedOut` 的 IO 错误
+    /// 超时的请求的回复到达后会被丢弃，连接仍可继续使用
+    pub fn with_timeout(mut self, timeout: Duration) -> Client {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub async fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
+        let frame = Ping::new(msg).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(value) => Ok(value.into()),
+            Frame::Bulk(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn echo(&mut self, msg: Bytes) -> crate::Result<Bytes> {
+        let frame = Echo::new(msg).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
+    }
+
     pub async fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
         let frame = Get::new(key).into_frame();
         debug!(request=?frame);
 
-        self.connection.write_frame(&frame).await?;
+        self.write_request(&frame).await?;
 
         match self.read_response().await? {
             Frame::Simple(value) => Ok(Some(value.into())),
@@ -51,10 +166,185 @@ impl Client {
         }
     }
 
+    /// 读取字符串值并按 UTF-8 解码，值不是合法的 UTF-8 时返回错误
+    pub async fn get_string(&mut self, key: &str) -> crate::Result<Option<String>> {
+        match self.get(key).await? {
+            Some(value) => match String::from_utf8(value.to_vec()) {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(format!("value of key '{}' is not valid UTF-8", key).into()),
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub async fn mget(&mut self, keys: &[&str]) -> crate::Result<Vec<Option<Bytes>>> {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        let frame = MGet::new(keys).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Frame::Bulk(value) => Ok(Some(value)),
+                    Frame::Null => Ok(None),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn strlen(&mut self, key: &str) -> crate::Result<u64> {
+        let frame = Strlen::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 从游标处开始遍历键，返回下一次遍历的游标和本次遍历到的键，游标为 0 表示遍历结束
+    pub async fn scan(
+        &mut self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> crate::Result<(u64, Vec<String>)> {
+        let frame = Scan::new(cursor, pattern.map(str::to_string), count).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(response) => match response.as_slice() {
+                [Frame::Bulk(next), Frame::Array(keys)] => {
+                    let next = std::str::from_utf8(next)
+                        .ok()
+                        .and_then(|next| next.parse().ok())
+                        .ok_or("protocol error; invalid cursor")?;
+                    Ok((next, keys.iter().map(|key| key.to_string()).collect()))
+                }
+                _ => Err(Frame::Array(response).to_error()),
+            },
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn dbsize(&mut self) -> crate::Result<u64> {
+        let frame = DbSize::new().into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn flushdb(&mut self) -> crate::Result<()> {
+        let frame = FlushDb::new().into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 切换当前连接使用的数据库，只对当前连接生效
+    pub async fn select(&mut self, index: i64) -> crate::Result<()> {
+        let frame = Select::new(index).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 原子地交换两个数据库的内容
+    pub async fn swapdb(&mut self, first: i64, second: i64) -> crate::Result<()> {
+        let frame = SwapDb::new(first, second).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn keys(&mut self, pattern: &str) -> crate::Result<Vec<String>> {
+        let frame = Keys::new(pattern).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(keys) => Ok(keys.iter().map(|key| key.to_string()).collect()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn getrange(&mut self, key: &str, start: i64, end: i64) -> crate::Result<Bytes> {
+        let frame = GetRange::new(key, start, end).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn getdel(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        let frame = GetDel::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn getset(&mut self, key: &str, value: Bytes) -> crate::Result<Option<Bytes>> {
+        let frame = GetSet::new(key, value).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
     pub async fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
         self.set_cmd(Set::new(key, value, None)).await
     }
 
+    pub async fn set_string(&mut self, key: &str, value: &str) -> crate::Result<()> {
+        self.set(key, Bytes::copy_from_slice(value.as_bytes()))
+            .await
+    }
+
     pub async fn set_expires(
         &mut self,
         key: &str,
@@ -64,12 +354,36 @@ impl Client {
         self.set_cmd(Set::new(key, value, Some(expires))).await
     }
 
+    pub async fn set_ex(&mut self, key: &str, seconds: u64, value: Bytes) -> crate::Result<()> {
+        let frame = SetEx::new(key, seconds as i64, value).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn pset_ex(&mut self, key: &str, millis: u64, value: Bytes) -> crate::Result<()> {
+        let frame = PSetEx::new(key, millis as i64, value).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
     async fn set_cmd(&mut self, set: Set) -> crate::Result<()> {
         let frame = set.into_frame();
 
         debug!(request=?frame);
 
-        self.connection.write_frame(&frame).await?;
+        self.write_request(&frame).await?;
 
         match self.read_response().await? {
             Frame::Simple(response) => {
@@ -84,11 +398,11 @@ impl Client {
         }
     }
 
-    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
-        let frame = Publish::new(channel, message).info_frame();
+    pub async fn incr(&mut self, key: &str) -> crate::Result<i64> {
+        let frame = Incr::new(key).into_frame();
         debug!(request=?frame);
 
-        self.connection.write_frame(&frame).await?;
+        self.write_request(&frame).await?;
 
         match self.read_response().await? {
             Frame::Integer(response) => Ok(response),
@@ -96,123 +410,1356 @@ impl Client {
         }
     }
 
-    pub async fn subscribe(mut self, channels: Vec<String>) -> crate::Result<Subscriber> {
-        self.subscribe_cmd(&channels).await?;
-        Ok(Subscriber {
-            client: self,
-            subscribed_channels: channels,
-        })
+    pub async fn incr_by_float(&mut self, key: &str, delta: f64) -> crate::Result<f64> {
+        let frame = IncrByFloat::new(key, delta).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => std::str::from_utf8(&value)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .ok_or_else(|| "protocol error; invalid float".into()),
+            frame => Err(frame.to_error()),
+        }
     }
 
-    async fn subscribe_cmd(&mut self, channels: &[String]) -> crate::Result<()> {
-        let frame = Subscribe::new(channels).into_frame();
+    pub async fn decr(&mut self, key: &str) -> crate::Result<i64> {
+        let frame = Decr::new(key).into_frame();
         debug!(request=?frame);
 
-        self.connection.write_frame(&frame).await?;
+        self.write_request(&frame).await?;
 
-        for channel in channels {
-            let response = self.read_response().await?;
-            match response {
-                Frame::Array(ref frame) => match frame.as_slice() {
-                    [subscribe, schannel, ..]
-                        if *subscribe == "subscribe" && *schannel == channel => {}
-                    _ => return Err(response.to_error()),
-                },
-                frame => return Err(frame.to_error()),
-            };
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response),
+            frame => Err(frame.to_error()),
         }
-        Ok(())
     }
 
-    async fn read_response(&mut self) -> crate::Result<Frame> {
-        let response = self.connection.read_frame().await?;
-        debug!(?response);
-        match response {
-            Some(Frame::Error(msg)) => Err(msg.into()),
-            Some(frame) => Ok(frame),
-            None => {
-                let err = Error::new(ErrorKind::ConnectionReset, "connection reset by server");
-                Err(err.into())
-            }
+    pub async fn append(&mut self, key: &str, value: Bytes) -> crate::Result<u64> {
+        let frame = Append::new(key, value).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
         }
     }
-}
 
-impl Subscriber {
-    pub fn get_subscribed(&self) -> &[String] {
-        &self.subscribed_channels
-    }
+    pub async fn mset(&mut self, pairs: &[(&str, Bytes)]) -> crate::Result<()> {
+        let pairs = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        let frame = MSet::new(pairs).into_frame();
+        debug!(request=?frame);
 
-    pub async fn next_message(&mut self) -> crate::Result<Option<Message>> {
-        match self.client.connection.read_frame().await? {
-            Some(mframe) => {
-                debug!(?mframe);
-                match mframe {
-                    Frame::Array(ref frame) => match frame.as_slice() {
-                        [message, channel, content] if *message == "message" => Ok(Some(Message {
-                            channel: channel.to_string(),
-                            content: Bytes::from(content.to_string()),
-                        })),
-                        _ => Err(mframe.to_error()),
-                    },
-                    frame => Err(frame.to_error()),
-                }
-            }
-            None => Ok(None),
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
         }
     }
 
-    pub fn into_stream(mut self) -> impl Stream<Item = crate::Result<Message>> {
-        try_stream! {
-            while let Some(message) = self.next_message().await?{
-                yield message;
-            }
+    pub async fn set_nx(&mut self, key: &str, value: Bytes) -> crate::Result<bool> {
+        let frame = SetNx::new(key, value).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
         }
     }
 
-    pub async fn subscribe(&mut self, channels: &[String]) -> crate::Result<()> {
-        self.client.subscribe_cmd(channels).await?;
-        self.subscribed_channels
-            .extend(channels.iter().map(Clone::clone));
-        Ok(())
-    }
+    pub async fn msetnx(&mut self, pairs: &[(&str, Bytes)]) -> crate::Result<bool> {
+        let pairs = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        let frame = MSetNx::new(pairs).into_frame();
+        debug!(request=?frame);
 
-    pub async fn unsubscribe(&mut self, channels: &[String]) -> crate::Result<()> {
-        let frame = Unsubscribe::new(channels).into_frame();
+        self.write_request(&frame).await?;
 
-        debug!(request=?frame);
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
 
-        self.client.connection.write_frame(&frame).await?;
+    pub async fn del(&mut self, keys: &[&str]) -> crate::Result<u64> {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        let frame = Del::new(keys).into_frame();
+        debug!(request=?frame);
 
-        let num = if channels.is_empty() {
-            self.subscribed_channels.len()
-        } else {
-            channels.len()
-        };
+        self.write_request(&frame).await?;
 
-        for _ in 0..num {
-            let response = self.client.read_response().await?;
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
 
-            match response {
-                Frame::Array(ref frame) => match frame.as_slice() {
-                    [unsubscribe, channel, ..] if *unsubscribe == "unsubscribe" => {
-                        let len = self.subscribed_channels.len();
-                        if len == 0 {
-                            return Err(response.to_error());
-                        }
+    pub async fn exists(&mut self, keys: &[&str]) -> crate::Result<u64> {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        let frame = Exists::new(keys).into_frame();
+        debug!(request=?frame);
 
-                        self.subscribed_channels.retain(|c| *channel != &c[..]);
+        self.write_request(&frame).await?;
 
-                        if self.subscribed_channels.len() != len - 1 {
-                            return Err(response.to_error());
-                        }
-                    }
-                    _ => return Err(response.to_error()),
-                },
-                frame => return Err(frame.to_error()),
-            }
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
         }
+    }
 
-        Ok(())
+    pub async fn expire(&mut self, key: &str, ttl: Duration) -> crate::Result<bool> {
+        let frame = Expire::new(key, ttl.as_secs() as i64).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn persist(&mut self, key: &str) -> crate::Result<bool> {
+        let frame = Persist::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn rename(&mut self, key: &str, new_key: &str) -> crate::Result<()> {
+        let frame = Rename::new(key, new_key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn rename_nx(&mut self, key: &str, new_key: &str) -> crate::Result<bool> {
+        let frame = RenameNx::new(key, new_key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将键连同过期时间移动到 `db` 号数据库，返回是否进行了移动
+    pub async fn move_key(&mut self, key: &str, db: i64) -> crate::Result<bool> {
+        let frame = Move::new(key, db).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn type_of(&mut self, key: &str) -> crate::Result<String> {
+        let frame = Type::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) => Ok(response),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn ttl(&mut self, key: &str) -> crate::Result<i64> {
+        let frame = Ttl::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn lpush(&mut self, key: &str, values: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = LPush::new(key, values).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn rpush(&mut self, key: &str, values: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = RPush::new(key, values).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 从列表的头部弹出元素。不指定数量时最多弹出一个，键不存在时返回空列表
+    pub async fn lpop(&mut self, key: &str, count: Option<u64>) -> crate::Result<Vec<Bytes>> {
+        let frame = LPop::new(key, count).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(vec![value]),
+            Frame::Null => Ok(vec![]),
+            Frame::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Frame::Bulk(value) => Ok(value),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 从列表的尾部弹出元素。不指定数量时最多弹出一个，键不存在时返回空列表
+    pub async fn rpop(&mut self, key: &str, count: Option<u64>) -> crate::Result<Vec<Bytes>> {
+        let frame = RPop::new(key, count).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(vec![value]),
+            Frame::Null => Ok(vec![]),
+            Frame::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Frame::Bulk(value) => Ok(value),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn llen(&mut self, key: &str) -> crate::Result<u64> {
+        let frame = LLen::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn lindex(&mut self, key: &str, index: i64) -> crate::Result<Option<Bytes>> {
+        let frame = LIndex::new(key, index).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn lrange(&mut self, key: &str, start: i64, stop: i64) -> crate::Result<Vec<Bytes>> {
+        let frame = LRange::new(key, start, stop).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(values) => values
+                .into_iter()
+                .map(|value| match value {
+                    Frame::Bulk(value) => Ok(value),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn hset(&mut self, key: &str, pairs: Vec<(Bytes, Bytes)>) -> crate::Result<u64> {
+        let frame = HSet::new(key, pairs).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn hget(&mut self, key: &str, field: Bytes) -> crate::Result<Option<Bytes>> {
+        let frame = HGet::new(key, field).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn hdel(&mut self, key: &str, fields: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = HDel::new(key, fields).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回哈希中所有的字段和值。RESP3 下的映射和 RESP2 下的扁平数组都按字段和值的对返回
+    pub async fn hgetall(&mut self, key: &str) -> crate::Result<Vec<(Bytes, Bytes)>> {
+        let frame = HGetAll::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        let pairs = match self.read_response().await? {
+            Frame::Map(pairs) => pairs,
+            Frame::Array(items) if items.len() % 2 == 0 => {
+                let mut items = items.into_iter();
+                let mut pairs = vec![];
+                while let (Some(field), Some(value)) = (items.next(), items.next()) {
+                    pairs.push((field, value));
+                }
+                pairs
+            }
+            frame => return Err(frame.to_error()),
+        };
+        pairs
+            .into_iter()
+            .map(|pair| match pair {
+                (Frame::Bulk(field), Frame::Bulk(value)) => Ok((field, value)),
+                (field, _) => Err(field.to_error()),
+            })
+            .collect()
+    }
+
+    pub async fn sadd(&mut self, key: &str, members: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = SAdd::new(key, members).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn srem(&mut self, key: &str, members: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = SRem::new(key, members).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn smembers(&mut self, key: &str) -> crate::Result<Vec<Bytes>> {
+        let frame = SMembers::new(key).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Set(members) | Frame::Array(members) => members
+                .into_iter()
+                .map(|member| match member {
+                    Frame::Bulk(member) => Ok(member),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn sismember(&mut self, key: &str, member: Bytes) -> crate::Result<bool> {
+        let frame = SIsMember::new(key, member).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
+        let frame = Publish::new(channel, message).info_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response as u64),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn pubsub_channels(&mut self, pattern: Option<&str>) -> crate::Result<Vec<String>> {
+        let frame = PubSub::channels(pattern.map(str::to_string)).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(channels) => {
+                Ok(channels.iter().map(|channel| channel.to_string()).collect())
+            }
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn pubsub_numsub(
+        &mut self,
+        channels: &[String],
+    ) -> crate::Result<Vec<(String, u64)>> {
+        let frame = PubSub::numsub(channels.to_vec()).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(response) => response
+                .chunks(2)
+                .map(|pair| match pair {
+                    [channel, Frame::Integer(num)] => Ok((channel.to_string(), *num as u64)),
+                    _ => Err("protocol error; invalid pubsub numsub response".into()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn subscribe(mut self, channels: Vec<String>) -> crate::Result<Subscriber> {
+        self.subscribe_cmd(&channels).await?;
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: channels,
+            subscribed_patterns: vec![],
+        })
+    }
+
+    /// 按 glob 模式订阅频道，返回的 `Subscriber` 收到的消息中带有匹配的模式
+    pub async fn psubscribe(mut self, patterns: Vec<String>) -> crate::Result<Subscriber> {
+        self.psubscribe_cmd(&patterns).await?;
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: vec![],
+            subscribed_patterns: patterns,
+        })
+    }
+
+    async fn subscribe_cmd(&mut self, channels: &[String]) -> crate::Result<()> {
+        // 服务器对每个频道各返回一个确认帧，没有频道时不会有任何确认，直接拒绝
+        if channels.is_empty() {
+            return Err("subscribe requires at least one channel".into());
+        }
+
+        let frame = Subscribe::new(channels).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+        self.read_subscribe_confirms("subscribe", channels).await
+    }
+
+    async fn psubscribe_cmd(&mut self, patterns: &[String]) -> crate::Result<()> {
+        if patterns.is_empty() {
+            return Err("psubscribe requires at least one pattern".into());
+        }
+
+        let frame = PSubscribe::new(patterns).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+        self.read_subscribe_confirms("psubscribe", patterns).await
+    }
+
+    /// 依次读取每个频道或模式的订阅确认帧
+    async fn read_subscribe_confirms(&mut self, kind: &str, names: &[String]) -> crate::Result<()> {
+        for name in names {
+            let response = self.read_response().await?;
+            match response {
+                Frame::Array(ref frame) => match frame.as_slice() {
+                    [subscribe, sname, Frame::Integer(n)]
+                        if *subscribe == kind
+                            && sname.as_bulk() == Some(name.as_bytes())
+                            && *n >= 0 => {}
+                    _ => return Err(response.to_error()),
+                },
+                frame => return Err(frame.to_error()),
+            };
+        }
+        Ok(())
+    }
+
+    pub async fn client_no_evict(&mut self, on: bool) -> crate::Result<()> {
+        let frame = ClientNoEvict::new(on).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn client_no_touch(&mut self, on: bool) -> crate::Result<()> {
+        let frame = ClientNoTouch::new(on).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 使用服务器通过 `--requirepass` 配置的密码认证当前连接
+    pub async fn auth(&mut self, password: &str) -> crate::Result<()> {
+        // 请求中包含密码，不输出到日志中
+        let frame = Auth::new(password).into_frame();
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 请求服务器关闭连接，等待服务器确认后释放客户端
+    pub async fn quit(mut self) -> crate::Result<()> {
+        let frame = Quit::new().into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn reset(&mut self) -> crate::Result<()> {
+        let frame = Reset::new().into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "RESET" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 协商协议版本，返回服务器信息的字段名和值。RESP2 下的扁平数组同样按键值对返回
+    pub async fn hello(&mut self, protover: Option<u64>) -> crate::Result<Vec<(String, Frame)>> {
+        let frame = Hello::new(protover).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Map(fields) => Ok(fields
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect()),
+            Frame::Array(fields) if fields.len() % 2 == 0 => {
+                let mut fields = fields.into_iter();
+                let mut out = vec![];
+                while let (Some(name), Some(value)) = (fields.next(), fields.next()) {
+                    out.push((name.to_string(), value));
+                }
+                Ok(out)
+            }
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn info(&mut self, sections: &[&str]) -> crate::Result<String> {
+        let sections = sections.iter().map(|section| section.to_string()).collect();
+        let frame = Info::new(sections).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(info) => Ok(String::from_utf8_lossy(&info).into_owned()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn command_all(&mut self) -> crate::Result<Vec<Frame>> {
+        let frame = CommandCmd::all().into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(commands) => Ok(commands),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn acl_log(&mut self, count: Option<u64>) -> crate::Result<Vec<AclLogEntry>> {
+        let frame = Acl::log(count).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(entries) => entries
+                .into_iter()
+                .map(|entry| match entry {
+                    Frame::Array(fields) => {
+                        let mut log = AclLogEntry::default();
+                        for pair in fields.chunks(2) {
+                            if let [name, value] = pair {
+                                let value = value.to_string();
+                                match name.to_string().as_str() {
+                                    "reason" => log.reason = value,
+                                    "object" => log.object = value,
+                                    "username" => log.username = value,
+                                    "age-seconds" => log.age_seconds = value.parse()?,
+                                    "client-info" => log.client_info = value,
+                                    _ => {}
+                                }
+                            }
+                        }
+                        Ok(log)
+                    }
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    pub async fn acl_cat(&mut self, category: Option<&str>) -> crate::Result<Vec<String>> {
+        let frame = Acl::cat(category.map(|category| category.to_string())).into_frame();
+        debug!(request=?frame);
+
+        self.write_request(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(names) => Ok(names.iter().map(|name| name.to_string()).collect()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 创建一个流水线，命令在调用 `execute` 时才会发送
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            client: self,
+            frames: vec![],
+        }
+    }
+
+    /// 连接是否已经因为读写错误或被服务器关闭而失效
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// 写入超时时请求可能只发送了一部分，因此任何写入错误都会使连接失效
+    async fn write_request(&mut self, frame: &Frame) -> crate::Result<()> {
+        let res = within(self.timeout, self.connection.write_frame(frame)).await;
+        self.broken |= res.is_err();
+        res
+    }
+
+    /// 读取一个回复，先丢弃之前超时的请求迟到的回复，保证回复与请求一一对应
+    async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
+        loop {
+            match within(self.timeout, self.connection.read_frame()).await {
+                Ok(Some(_)) if self.pending > 0 => self.pending -= 1,
+                Err(err) if is_timeout(&err) => {
+                    self.pending += 1;
+                    return Err(err);
+                }
+                res => {
+                    self.broken |= !matches!(res, Ok(Some(_)));
+                    return res;
+                }
+            }
+        }
+    }
+
+    async fn read_response(&mut self) -> crate::Result<Frame> {
+        let response = self.read_frame().await?;
+        debug!(?response);
+        match response {
+            Some(Frame::Error(msg)) => Err(msg.into()),
+            Some(frame) => Ok(frame),
+            None => {
+                let err = Error::new(ErrorKind::ConnectionReset, "connection reset by server");
+                Err(err.into())
+            }
+        }
+    }
+}
+
+impl Pipeline<'_> {
+    pub fn get(&mut self, key: &str) -> &mut Self {
+        self.push(Get::new(key).into_frame())
+    }
+
+    pub fn set(&mut self, key: &str, value: Bytes) -> &mut Self {
+        self.push(Set::new(key, value, None).into_frame())
+    }
+
+    pub fn set_expires(&mut self, key: &str, value: Bytes, expires: Duration) -> &mut Self {
+        self.push(Set::new(key, value, Some(expires)).into_frame())
+    }
+
+    pub fn del(&mut self, keys: &[&str]) -> &mut Self {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        self.push(Del::new(keys).into_frame())
+    }
+
+    pub fn exists(&mut self, keys: &[&str]) -> &mut Self {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+        self.push(Exists::new(keys).into_frame())
+    }
+
+    pub fn incr(&mut self, key: &str) -> &mut Self {
+        self.push(Incr::new(key).into_frame())
+    }
+
+    pub fn decr(&mut self, key: &str) -> &mut Self {
+        self.push(Decr::new(key).into_frame())
+    }
+
+    pub fn expire(&mut self, key: &str, ttl: Duration) -> &mut Self {
+        self.push(Expire::new(key, ttl.as_secs() as i64).into_frame())
+    }
+
+    pub fn lpush(&mut self, key: &str, values: Vec<Bytes>) -> &mut Self {
+        self.push(LPush::new(key, values).into_frame())
+    }
+
+    pub fn rpush(&mut self, key: &str, values: Vec<Bytes>) -> &mut Self {
+        self.push(RPush::new(key, values).into_frame())
+    }
+
+    pub fn hset(&mut self, key: &str, pairs: Vec<(Bytes, Bytes)>) -> &mut Self {
+        self.push(HSet::new(key, pairs).into_frame())
+    }
+
+    pub fn sadd(&mut self, key: &str, members: Vec<Bytes>) -> &mut Self {
+        self.push(SAdd::new(key, members).into_frame())
+    }
+
+    /// 已缓存但尚未发送的命令数量
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 发送所有缓存的命令，返回的回复与命令的缓存顺序一一对应
+    /// 单条命令执行出错不会中断流水线，对应位置的回复为 `Frame::Error`
+    pub async fn execute(&mut self) -> crate::Result<Vec<Frame>> {
+        let frames = std::mem::take(&mut self.frames);
+        debug!(request=?frames);
+
+        let client = &mut *self.client;
+        let res = within(client.timeout, client.connection.write_frames(&frames)).await;
+        client.broken |= res.is_err();
+        res?;
+
+        let mut responses = Vec::with_capacity(frames.len());
+        for i in 0..frames.len() {
+            let response = match client.read_frame().await {
+                Ok(response) => response,
+                Err(err) => {
+                    // 剩余命令的回复也会迟到，同样需要丢弃
+                    if is_timeout(&err) {
+                        client.pending += frames.len() - i - 1;
+                    }
+                    return Err(err);
+                }
+            };
+            debug!(?response);
+            match response {
+                Some(frame) => responses.push(frame),
+                None => {
+                    let err = Error::new(ErrorKind::ConnectionReset, "connection reset by server");
+                    return Err(err.into());
+                }
+            }
+        }
+        Ok(responses)
+    }
+
+    fn push(&mut self, frame: Frame) -> &mut Self {
+        self.frames.push(frame);
+        self
+    }
+}
+
+impl Subscriber {
+    pub fn get_subscribed(&self) -> &[String] {
+        &self.subscribed_channels
+    }
+
+    pub fn get_subscribed_patterns(&self) -> &[String] {
+        &self.subscribed_patterns
+    }
+
+    /// 读取下一条消息，连接关闭时返回 `None`
+    ///
+    /// 处理过慢时服务器会通知有消息被丢弃，订阅仍然有效，因此只记录警告并继续读取
+    pub async fn next_message(&mut self) -> crate::Result<Option<Message>> {
+        loop {
+            let mframe = match self.client.connection.read_frame().await? {
+                Some(mframe) => mframe,
+                None => return Ok(None),
+            };
+            debug!(?mframe);
+            return match mframe {
+                Frame::Array(ref frame) => match frame.as_slice() {
+                    // 消息内容可能是任意二进制数据，直接使用原始字节
+                    [message, channel, Frame::Bulk(content)] if *message == "message" => {
+                        Ok(Some(Message {
+                            channel: frame_to_name(channel),
+                            content: content.clone(),
+                            pattern: None,
+                        }))
+                    }
+                    [pmessage, pattern, channel, Frame::Bulk(content)]
+                        if *pmessage == "pmessage" =>
+                    {
+                        Ok(Some(Message {
+                            channel: frame_to_name(channel),
+                            content: content.clone(),
+                            pattern: Some(frame_to_name(pattern)),
+                        }))
+                    }
+                    _ => Err(mframe.to_error()),
+                },
+                Frame::Error(msg) if msg.starts_with(LAGGED_PREFIX) => {
+                    warn!(%msg, "subscriber lagged, messages were dropped");
+                    continue;
+                }
+                frame => Err(frame.to_error()),
+            };
+        }
+    }
+
+    pub fn into_stream(mut self) -> impl Stream<Item = crate::Result<Message>> {
+        try_stream! {
+            while let Some(message) = self.next_message().await?{
+                yield message;
+            }
+        }
+    }
+
+    pub async fn subscribe(&mut self, channels: &[String]) -> crate::Result<()> {
+        self.client.subscribe_cmd(channels).await?;
+        self.subscribed_channels
+            .extend(channels.iter().map(Clone::clone));
+        Ok(())
+    }
+
+    pub async fn unsubscribe(&mut self, channels: &[String]) -> crate::Result<()> {
+        let frame = Unsubscribe::new(channels).into_frame();
+
+        debug!(request=?frame);
+
+        self.client.write_request(&frame).await?;
+
+        let num = if channels.is_empty() {
+            self.subscribed_channels.len()
+        } else {
+            channels.len()
+        };
+        read_unsubscribe_confirms(
+            &mut self.client,
+            "unsubscribe",
+            &mut self.subscribed_channels,
+            num,
+        )
+        .await
+    }
+
+    pub async fn psubscribe(&mut self, patterns: &[String]) -> crate::Result<()> {
+        self.client.psubscribe_cmd(patterns).await?;
+        self.subscribed_patterns
+            .extend(patterns.iter().map(Clone::clone));
+        Ok(())
+    }
+
+    pub async fn punsubscribe(&mut self, patterns: &[String]) -> crate::Result<()> {
+        let frame = PUnsubscribe::new(patterns).into_frame();
+
+        debug!(request=?frame);
+
+        self.client.write_request(&frame).await?;
+
+        let num = if patterns.is_empty() {
+            self.subscribed_patterns.len()
+        } else {
+            patterns.len()
+        };
+        read_unsubscribe_confirms(
+            &mut self.client,
+            "punsubscribe",
+            &mut self.subscribed_patterns,
+            num,
+        )
+        .await
+    }
+}
+
+/// 与服务器 `accept` 的退避方式一致：从 1 秒开始每次翻倍，最长等待 64 秒
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 7,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(64),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 按策略重试建立连接，超过最大次数后返回最后一次的错误
+    async fn dial(&self, addr: &str) -> crate::Result<Client> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            match connect(addr).await {
+                Ok(client) => return Ok(client),
+                Err(err) => {
+                    attempt += 1;
+                    if attempt >= self.max_retries {
+                        return Err(err);
+                    }
+                    debug!(cause=%err, ?backoff, "reconnecting");
+                }
+            }
+            time::sleep(backoff).await;
+
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+}
+
+impl ReconnectingClient {
+    /// 连接到 `addr`，之后连接断开时按 `policy` 重新连接
+    pub async fn connect(addr: &str, policy: RetryPolicy) -> crate::Result<ReconnectingClient> {
+        let client = policy.dial(addr).await?;
+        Ok(ReconnectingClient {
+            addr: addr.to_string(),
+            policy,
+            client: Some(client),
+        })
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: RetryPolicy) {
+        self.policy = policy;
+    }
+
+    /// 返回当前的连接，上次操作因连接错误失败时先重新连接
+    /// 通过返回的 `Client` 执行的命令失败后不会自动重试，也不会标记连接失效
+    pub async fn client(&mut self) -> crate::Result<&mut Client> {
+        let client = match self.client.take() {
+            Some(client) => client,
+            None => self.policy.dial(&self.addr).await?,
+        };
+        Ok(self.client.insert(client))
+    }
+
+    pub async fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
+        self.retry(async |client| client.ping(msg.clone()).await)
+            .await
+    }
+
+    pub async fn get(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        self.retry(async |client| client.get(key).await).await
+    }
+
+    pub async fn mget(&mut self, keys: &[&str]) -> crate::Result<Vec<Option<Bytes>>> {
+        self.retry(async |client| client.mget(keys).await).await
+    }
+
+    pub async fn strlen(&mut self, key: &str) -> crate::Result<u64> {
+        self.retry(async |client| client.strlen(key).await).await
+    }
+
+    pub async fn exists(&mut self, keys: &[&str]) -> crate::Result<u64> {
+        self.retry(async |client| client.exists(keys).await).await
+    }
+
+    pub async fn ttl(&mut self, key: &str) -> crate::Result<i64> {
+        self.retry(async |client| client.ttl(key).await).await
+    }
+
+    pub async fn type_of(&mut self, key: &str) -> crate::Result<String> {
+        self.retry(async |client| client.type_of(key).await).await
+    }
+
+    pub async fn llen(&mut self, key: &str) -> crate::Result<u64> {
+        self.retry(async |client| client.llen(key).await).await
+    }
+
+    pub async fn lrange(&mut self, key: &str, start: i64, stop: i64) -> crate::Result<Vec<Bytes>> {
+        self.retry(async |client| client.lrange(key, start, stop).await)
+            .await
+    }
+
+    pub async fn hget(&mut self, key: &str, field: Bytes) -> crate::Result<Option<Bytes>> {
+        self.retry(async |client| client.hget(key, field.clone()).await)
+            .await
+    }
+
+    pub async fn hgetall(&mut self, key: &str) -> crate::Result<Vec<(Bytes, Bytes)>> {
+        self.retry(async |client| client.hgetall(key).await).await
+    }
+
+    pub async fn smembers(&mut self, key: &str) -> crate::Result<Vec<Bytes>> {
+        self.retry(async |client| client.smembers(key).await).await
+    }
+
+    /// 写命令不是幂等的，连接错误时不重试，只在下一次调用时重新连接
+    pub async fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
+        self.once(async |client| client.set(key, value).await).await
+    }
+
+    pub async fn del(&mut self, keys: &[&str]) -> crate::Result<u64> {
+        self.once(async |client| client.del(keys).await).await
+    }
+
+    pub async fn incr(&mut self, key: &str) -> crate::Result<i64> {
+        self.once(async |client| client.incr(key).await).await
+    }
+
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
+        self.once(async |client| client.publish(channel, message).await)
+            .await
+    }
+
+    /// 订阅频道，返回的订阅者在连接断开后会自动重连并重新订阅
+    pub async fn subscribe(
+        mut self,
+        channels: Vec<String>,
+    ) -> crate::Result<ReconnectingSubscriber> {
+        let client = match self.client.take() {
+            Some(client) => client,
+            None => self.policy.dial(&self.addr).await?,
+        };
+        Ok(ReconnectingSubscriber {
+            subscriber: client.subscribe(channels).await?,
+            addr: self.addr,
+            policy: self.policy,
+        })
+    }
+
+    /// 执行幂等的命令，因连接错误失败时重新连接并重试
+    async fn retry<T>(
+        &mut self,
+        mut op: impl AsyncFnMut(&mut Client) -> crate::Result<T>,
+    ) -> crate::Result<T> {
+        let mut attempt = 0;
+        loop {
+            let client = self.client().await?;
+            match op(client).await {
+                Err(err) if is_connection_error(&err) => {
+                    self.client = None;
+                    attempt += 1;
+                    if attempt >= self.policy.max_retries {
+                        return Err(err);
+                    }
+                    debug!(cause=%err, "connection lost, retrying");
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// 执行一次命令，因连接错误失败时丢弃连接
+    async fn once<T>(
+        &mut self,
+        op: impl AsyncFnOnce(&mut Client) -> crate::Result<T>,
+    ) -> crate::Result<T> {
+        let client = self.client().await?;
+        let res = op(client).await;
+        if let Err(err) = &res
+            && is_connection_error(err)
+        {
+            self.client = None;
+        }
+        res
+    }
+}
+
+impl ReconnectingSubscriber {
+    /// 返回当前的订阅者，通过它增减的频道和模式在重连后同样会重新订阅
+    pub fn subscriber(&mut self) -> &mut Subscriber {
+        &mut self.subscriber
+    }
+
+    /// 读取下一条消息，连接断开时重新连接并订阅原有的频道和模式后继续等待
+    pub async fn next_message(&mut self) -> crate::Result<Option<Message>> {
+        loop {
+            match self.subscriber.next_message().await {
+                Ok(Some(message)) => return Ok(Some(message)),
+                Err(err) if !is_connection_error(&err) => return Err(err),
+                res => debug!(?res, "subscription lost, reconnecting"),
+            }
+            self.resubscribe().await?;
+        }
+    }
+
+    async fn resubscribe(&mut self) -> crate::Result<()> {
+        let mut subscriber = Subscriber {
+            client: self.policy.dial(&self.addr).await?,
+            subscribed_channels: vec![],
+            subscribed_patterns: vec![],
+        };
+        if !self.subscriber.subscribed_channels.is_empty() {
+            subscriber
+                .subscribe(&self.subscriber.subscribed_channels)
+                .await?;
+        }
+        if !self.subscriber.subscribed_patterns.is_empty() {
+            subscriber
+                .psubscribe(&self.subscriber.subscribed_patterns)
+                .await?;
+        }
+        self.subscriber = subscriber;
+        Ok(())
+    }
+}
+
+/// 在 `timeout` 内等待 `fut` 完成，超时返回 `ErrorKind::TimedOut` 的 IO 错误
+async fn within<T, E>(
+    timeout: Option<Duration>,
+    fut: impl Future<Output = Result<T, E>>,
+) -> crate::Result<T>
+where
+    E: Into<crate::Error>,
+{
+    let res = match timeout {
+        Some(timeout) => match time::timeout(timeout, fut).await {
+            Ok(res) => res,
+            Err(_) => return Err(Error::new(ErrorKind::TimedOut, "operation timed out").into()),
+        },
+        None => fut.await,
+    };
+    res.map_err(Into::into)
+}
+
+fn is_timeout(err: &crate::Error) -> bool {
+    err.downcast_ref::<Error>()
+        .is_some_and(|err| err.kind() == ErrorKind::TimedOut)
+}
+
+/// 底层 IO 出错或连接被关闭时，认为连接已经失效
+fn is_connection_error(err: &crate::Error) -> bool {
+    err.downcast_ref::<Error>().is_some() || err.is::<ConnectionReset>()
+}
+
+/// 读取 `num` 个退订确认帧，并从 `subscribed` 中移除对应的频道或模式
+async fn read_unsubscribe_confirms(
+    client: &mut Client,
+    kind: &str,
+    subscribed: &mut Vec<String>,
+    num: usize,
+) -> crate::Result<()> {
+    for _ in 0..num {
+        let response = client.read_response().await?;
+
+        match response {
+            Frame::Array(ref frame) => match frame.as_slice() {
+                [unsubscribe, name, ..] if *unsubscribe == kind => {
+                    let len = subscribed.len();
+                    if len == 0 {
+                        return Err(response.to_error());
+                    }
+
+                    subscribed.retain(|c| *name != &c[..]);
+
+                    if subscribed.len() != len - 1 {
+                        return Err(response.to_error());
+                    }
+                }
+                _ => return Err(response.to_error()),
+            },
+            frame => return Err(frame.to_error()),
+        }
+    }
+
+    Ok(())
+}
+
+/// 频道名和模式可能包含任意字节，按 UTF-8 有损解码
+fn frame_to_name(frame: &Frame) -> String {
+    match frame.as_bulk() {
+        Some(name) => String::from_utf8_lossy(name).into_owned(),
+        None => frame.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server;
+    use tokio::net::TcpListener;
+
+    // 用内存管道连接客户端，返回的连接代表服务器一端
+    fn client_pair() -> (Client, Connection) {
+        let (client, server) = tokio::io::duplex(4096);
+        let client = Client {
+            connection: Connection::new(Box::new(client)),
+            timeout: None,
+            pending: 0,
+            broken: false,
+        };
+        (client, Connection::new(Box::new(server)))
+    }
+
+    fn message_frame(channel: &str, content: &'static [u8]) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from_static(b"message"));
+        frame.push_bulk(Bytes::from(channel.to_string()));
+        frame.push_bulk(Bytes::from_static(content));
+        frame
+    }
+
+    #[tokio::test]
+    async fn lag_notice_does_not_end_subscription() {
+        let (client, mut server) = client_pair();
+        let mut subscriber = Subscriber {
+            client,
+            subscribed_channels: vec!["news".to_string()],
+            subscribed_patterns: vec![],
+        };
+
+        let lagged = Frame::Error(format!("{} on 'news', 3 messages dropped", LAGGED_PREFIX));
+        server
+            .write_frames(&[lagged, message_frame("news", b"after")])
+            .await
+            .unwrap();
+
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!(message.channel, "news");
+        assert_eq!(&message.content[..], b"after");
+    }
+
+    #[tokio::test]
+    async fn subscribe_rejects_negative_count() {
+        let (mut client, mut server) = client_pair();
+        let mut ack = Frame::array();
+        ack.push_bulk(Bytes::from_static(b"subscribe"));
+        ack.push_bulk(Bytes::from_static(b"news"));
+        ack.push_int(-1);
+        server.write_frame(&ack).await.unwrap();
+
+        let channels = ["news".to_string()];
+        let res = client.read_subscribe_confirms("subscribe", &channels).await;
+        assert!(res.is_err());
+    }
+
+    #[tokio::test]
+    async fn binary_message_round_trips() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(server::run(
+            listener,
+            server::Config::default(),
+            std::future::pending::<()>(),
+        ));
+
+        let mut subscriber = connect(addr)
+            .await
+            .unwrap()
+            .subscribe(vec!["bin".to_string()])
+            .await
+            .unwrap();
+        let mut publisher = connect(addr).await.unwrap();
+        let payload = Bytes::from_static(&[0xFF, 0x00, 0xFE]);
+        assert_eq!(publisher.publish("bin", payload.clone()).await.unwrap(), 1);
+
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        assert_eq!(message.channel, "bin");
+        assert_eq!(message.content, payload);
+    }
+
+    #[tokio::test]
+    async fn timeout_when_server_never_replies() {
+        // 只接受连接，从不回复
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let mut client = connect(addr)
+            .await
+            .unwrap()
+            .with_timeout(Duration::from_millis(100));
+        let started = time::Instant::now();
+        let err = client.ping(None).await.unwrap_err();
+        assert!(is_timeout(&err), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(1));
     }
 }
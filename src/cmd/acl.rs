@@ -0,0 +1,146 @@
+use crate::cmd::command::{ACL_CATEGORIES, commands_in_category};
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use std::time::SystemTime;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Acl {
+    subcommand: AclSubcommand,
+}
+
+#[derive(Debug)]
+pub enum AclSubcommand {
+    Log { count: Option<u64> },
+    LogReset,
+    Cat { category: Option<String> },
+}
+
+impl Acl {
+    pub(crate) fn log(count: Option<u64>) -> Acl {
+        Acl {
+            subcommand: AclSubcommand::Log { count },
+        }
+    }
+
+    pub(crate) fn cat(category: Option<String>) -> Acl {
+        Acl {
+            subcommand: AclSubcommand::Cat { category },
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Acl> {
+        use ParseError::EndOfStream;
+
+        let subcommand = parse.next_string()?.to_lowercase();
+        let subcommand = match &subcommand[..] {
+            "log" => match parse.next_string() {
+                Ok(s) if s.eq_ignore_ascii_case("reset") => AclSubcommand::LogReset,
+                Ok(s) if s.eq_ignore_ascii_case("count") => AclSubcommand::Log {
+                    count: Some(parse.next_int()?),
+                },
+                Ok(s) => match s.parse::<u64>() {
+                    Ok(count) => AclSubcommand::Log { count: Some(count) },
+                    Err(_) => return Err("ERR value is out of range, must be positive".into()),
+                },
+                Err(EndOfStream) => AclSubcommand::Log { count: None },
+                Err(e) => return Err(e.into()),
+            },
+            "cat" => match parse.next_string() {
+                Ok(category) => AclSubcommand::Cat {
+                    category: Some(category.to_lowercase()),
+                },
+                Err(EndOfStream) => AclSubcommand::Cat { category: None },
+                Err(e) => return Err(e.into()),
+            },
+            _ => return Err(format!("unknown `acl` subcommand '{}'", subcommand).into()),
+        };
+        Ok(Acl { subcommand })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            AclSubcommand::Log { count } => {
+                let now = SystemTime::now();
+                let entries = db
+                    .acl_log(count.map(|count| count as usize))
+                    .into_iter()
+                    .map(|entry| {
+                        let age = now
+                            .duration_since(entry.timestamp)
+                            .unwrap_or_default()
+                            .as_secs_f64();
+                        let mut frame = Frame::array();
+                        frame.push_bulk(Bytes::from_static(b"reason"));
+                        frame.push_bulk(Bytes::from_static(entry.reason.as_str().as_bytes()));
+                        frame.push_bulk(Bytes::from_static(b"object"));
+                        frame.push_bulk(Bytes::from(entry.call));
+                        frame.push_bulk(Bytes::from_static(b"username"));
+                        frame.push_bulk(Bytes::from(entry.username));
+                        frame.push_bulk(Bytes::from_static(b"age-seconds"));
+                        frame.push_bulk(Bytes::from(format!("{:.3}", age)));
+                        frame.push_bulk(Bytes::from_static(b"client-info"));
+                        frame.push_bulk(Bytes::from(entry.client_addr));
+                        frame
+                    })
+                    .collect();
+                Frame::Array(entries)
+            }
+            AclSubcommand::LogReset => {
+                db.acl_log_reset();
+                Frame::Simple("OK".to_string())
+            }
+            AclSubcommand::Cat { category: None } => Frame::Array(
+                ACL_CATEGORIES
+                    .iter()
+                    .map(|category| Frame::Bulk(Bytes::from_static(category.as_bytes())))
+                    .collect(),
+            ),
+            AclSubcommand::Cat {
+                category: Some(category),
+            } => {
+                if ACL_CATEGORIES.contains(&&category[..]) {
+                    Frame::Array(
+                        commands_in_category(&category)
+                            .into_iter()
+                            .map(|name| Frame::Bulk(Bytes::from_static(name.as_bytes())))
+                            .collect(),
+                    )
+                } else {
+                    Frame::Error(format!("ERR Unknown category '{}'", category))
+                }
+            }
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("acl".as_bytes()));
+        match self.subcommand {
+            AclSubcommand::Log { count } => {
+                frame.push_bulk(Bytes::from("log".as_bytes()));
+                if let Some(count) = count {
+                    frame.push_bulk(Bytes::from(count.to_string()));
+                }
+            }
+            AclSubcommand::LogReset => {
+                frame.push_bulk(Bytes::from("log".as_bytes()));
+                frame.push_bulk(Bytes::from("reset".as_bytes()));
+            }
+            AclSubcommand::Cat { category } => {
+                frame.push_bulk(Bytes::from("cat".as_bytes()));
+                if let Some(category) = category {
+                    frame.push_bulk(Bytes::from(category.into_bytes()));
+                }
+            }
+        }
+        frame
+    }
+}
@@ -0,0 +1,53 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Append {
+    key: String,
+    value: Bytes,
+}
+
+impl Append {
+    pub fn new(key: impl ToString, value: Bytes) -> Append {
+        Append {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Append> {
+        let key = parse.next_string()?;
+        let value = parse.next_bytes()?;
+        Ok(Append { key, value })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.append(&self.key, &self.value) {
+            Ok(value) => Frame::Integer(value as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("append".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(self.value);
+        frame
+    }
+}
+
+impl KeyExtractor for Append {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
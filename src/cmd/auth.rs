@@ -0,0 +1,88 @@
+use crate::connection::Connection;
+use crate::db::{AclDenyReason, AclLogEntry, DEFAULT_USER, Db};
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use std::fmt;
+use std::time::SystemTime;
+use tracing::debug;
+
+pub struct Auth {
+    username: Option<String>,
+    password: String,
+}
+
+impl Auth {
+    pub fn new(password: impl ToString) -> Auth {
+        Auth {
+            username: None,
+            password: password.to_string(),
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Auth> {
+        // 与 Redis 6 一致，同时支持 `AUTH password` 和 `AUTH username password`
+        let first = parse.next_string()?;
+        match parse.next_string() {
+            Ok(password) => Ok(Auth {
+                username: Some(first),
+                password,
+            }),
+            Err(ParseError::EndOfStream) => Ok(Auth {
+                username: None,
+                password: first,
+            }),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 密码正确时将当前连接标记为已认证，失败的尝试记录到 ACL 日志中
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let username = self.username.as_deref().unwrap_or(DEFAULT_USER);
+        let response = if !db.requires_auth() {
+            Frame::Error(
+                "ERR AUTH <password> called without any password configured for the default user. \
+                 Are you sure your configuration is correct?"
+                    .to_string(),
+            )
+        } else if username == DEFAULT_USER && db.check_password(&self.password) {
+            dst.state.authenticated = true;
+            Frame::Simple("OK".to_string())
+        } else {
+            db.acl_log_push(AclLogEntry {
+                timestamp: SystemTime::now(),
+                reason: AclDenyReason::Auth,
+                username: username.to_string(),
+                client_addr: dst.peer_addr.clone(),
+                call: "AUTH".to_string(),
+            });
+            Frame::Error(
+                "WRONGPASS invalid username-password pair or user is disabled.".to_string(),
+            )
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("auth".as_bytes()));
+        if let Some(username) = self.username {
+            frame.push_bulk(Bytes::from(username.into_bytes()));
+        }
+        frame.push_bulk(Bytes::from(self.password.into_bytes()));
+        frame
+    }
+}
+
+// 命令会以调试级别输出到日志中，不能包含密码
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Auth")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
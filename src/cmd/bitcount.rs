@@ -0,0 +1,101 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct BitCount {
+    key: String,
+    range: Option<(i64, i64, BitCountUnit)>,
+}
+
+/// `BITCOUNT` 中 start 和 end 的单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitCountUnit {
+    Byte,
+    Bit,
+}
+
+impl BitCount {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<BitCount> {
+        use ParseError::EndOfStream;
+
+        let key = parse.next_string()?;
+        let start = match parse.next_signed_int() {
+            Ok(start) => start,
+            Err(EndOfStream) => return Ok(BitCount { key, range: None }),
+            Err(e) => return Err(e.into()),
+        };
+        let end = match parse.next_signed_int() {
+            Ok(end) => end,
+            Err(EndOfStream) => return Err("ERR syntax error".into()),
+            Err(e) => return Err(e.into()),
+        };
+        let unit = match parse.next_string() {
+            Ok(s) if s.eq_ignore_ascii_case("byte") => BitCountUnit::Byte,
+            Ok(s) if s.eq_ignore_ascii_case("bit") => BitCountUnit::Bit,
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(EndOfStream) => BitCountUnit::Byte,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(BitCount {
+            key,
+            range: Some((start, end, unit)),
+        })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.bitcount(&self.key, self.range) {
+            Ok(value) => Frame::Integer(value as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl KeyExtractor for BitCount {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{DEFAULT_SET_MAX_LISTPACK_ENTRIES, DEFAULT_SET_MAX_LISTPACK_VALUE};
+    use bytes::Bytes;
+
+    fn parse(args: &[&str]) -> BitCount {
+        let mut frame = Frame::array();
+        for arg in args {
+            frame.push_bulk(Bytes::from(arg.to_string()));
+        }
+        let mut parse = Parse::new(frame).unwrap();
+        parse.next_string().unwrap();
+        BitCount::parse_frames(&mut parse).unwrap()
+    }
+
+    #[tokio::test]
+    async fn bit_range_matches_byte_range() {
+        let db = Db::new(
+            1,
+            16,
+            None,
+            DEFAULT_SET_MAX_LISTPACK_ENTRIES,
+            DEFAULT_SET_MAX_LISTPACK_VALUE,
+        );
+        db.set("key".to_string(), Bytes::from("foobar"), None, None, false);
+
+        let bit = parse(&["BITCOUNT", "key", "0", "7", "BIT"]);
+        let byte = parse(&["BITCOUNT", "key", "0", "0", "BYTE"]);
+        let bit = db.bitcount(&bit.key, bit.range).unwrap();
+        let byte = db.bitcount(&byte.key, byte.range).unwrap();
+        assert_eq!(bit, byte);
+        assert_eq!(bit, 4);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::connection::Connection;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct ClientNoEvict {
+    on: bool,
+}
+
+impl ClientNoEvict {
+    pub fn new(on: bool) -> ClientNoEvict {
+        ClientNoEvict { on }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ClientNoEvict> {
+        let on = match &parse.next_string()?.to_lowercase()[..] {
+            "on" => true,
+            "off" => false,
+            _ => return Err("ERR syntax error".into()),
+        };
+        Ok(ClientNoEvict { on })
+    }
+
+    /// 服务器没有内存上限，也就从不淘汰键，开关不影响任何行为，接受该命令只为兼容 Redis 客户端
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("client".as_bytes()));
+        frame.push_bulk(Bytes::from("no-evict".as_bytes()));
+        frame.push_bulk(Bytes::from(if self.on { "on" } else { "off" }.as_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,41 @@
+use crate::connection::Connection;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct ClientNoTouch {
+    on: bool,
+}
+
+impl ClientNoTouch {
+    pub fn new(on: bool) -> ClientNoTouch {
+        ClientNoTouch { on }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<ClientNoTouch> {
+        let on = match &parse.next_string()?.to_lowercase()[..] {
+            "on" => true,
+            "off" => false,
+            _ => return Err("ERR syntax error".into()),
+        };
+        Ok(ClientNoTouch { on })
+    }
+
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        dst.state.no_touch = self.on;
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("client".as_bytes()));
+        frame.push_bulk(Bytes::from("no-touch".as_bytes()));
+        frame.push_bulk(Bytes::from(if self.on { "on" } else { "off" }.as_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,52 @@
+use crate::connection::Connection;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Cluster {
+    subcommand: ClusterSubcommand,
+}
+
+#[derive(Debug)]
+pub enum ClusterSubcommand {
+    Info,
+}
+
+/// 服务器不支持集群模式，`CLUSTER INFO` 固定返回单节点、未启用集群的状态，
+/// 以便集群感知的客户端在启动探测时能够正常回退到单机模式
+const CLUSTER_INFO: &str = "cluster_enabled:0\r\n\
+    cluster_state:ok\r\n\
+    cluster_slots_assigned:0\r\n\
+    cluster_slots_ok:0\r\n\
+    cluster_slots_pfail:0\r\n\
+    cluster_slots_fail:0\r\n\
+    cluster_known_nodes:1\r\n\
+    cluster_size:0\r\n\
+    cluster_current_epoch:0\r\n\
+    cluster_my_epoch:0\r\n\
+    cluster_stats_messages_sent:0\r\n\
+    cluster_stats_messages_received:0\r\n\
+    total_cluster_links_buffer_limit_exceeded:0\r\n";
+
+impl Cluster {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Cluster> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        let subcommand = match &subcommand[..] {
+            "info" => ClusterSubcommand::Info,
+            _ => return Err(format!("unknown `cluster` subcommand '{}'", subcommand).into()),
+        };
+        Ok(Cluster { subcommand })
+    }
+
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            ClusterSubcommand::Info => Frame::Bulk(Bytes::from_static(CLUSTER_INFO.as_bytes())),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,349 @@
+use crate::cmd::Command;
+use crate::connection::Connection;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct CommandCmd {
+    subcommand: CommandSubcommand,
+}
+
+#[derive(Debug)]
+pub enum CommandSubcommand {
+    All,
+    Info { names: Vec<String> },
+    GetKeys { args: Vec<Frame> },
+    Unknown { name: String },
+}
+
+/// 命令的元数据，对应 `COMMAND INFO` 返回的各个字段
+#[derive(Debug)]
+pub(crate) struct CommandMeta {
+    pub(crate) name: &'static str,
+    /// 参数个数（包含命令名本身），负数表示至少需要 `-arity` 个参数
+    pub(crate) arity: i64,
+    pub(crate) flags: &'static [&'static str],
+    pub(crate) first_key: i64,
+    pub(crate) last_key: i64,
+    pub(crate) step: i64,
+    /// 命令所属的 ACL 分类
+    pub(crate) categories: &'static [&'static str],
+}
+
+const fn meta(
+    name: &'static str,
+    arity: i64,
+    flags: &'static [&'static str],
+    first_key: i64,
+    last_key: i64,
+    step: i64,
+    categories: &'static [&'static str],
+) -> CommandMeta {
+    CommandMeta {
+        name,
+        arity,
+        flags,
+        first_key,
+        last_key,
+        step,
+        categories,
+    }
+}
+
+/// 所有 ACL 分类的名称
+pub(crate) const ACL_CATEGORIES: &[&str] = &[
+    "keyspace",
+    "read",
+    "write",
+    "set",
+    "sortedset",
+    "list",
+    "hash",
+    "string",
+    "bitmap",
+    "hyperloglog",
+    "geo",
+    "stream",
+    "pubsub",
+    "admin",
+    "fast",
+    "slow",
+    "blocking",
+    "dangerous",
+    "connection",
+    "transaction",
+    "scripting",
+];
+
+// 常用的命令标志组合
+const ADMIN: &[&str] = &["admin", "noscript", "loading", "stale"];
+const AUTH: &[&str] = &["noscript", "loading", "stale", "fast", "no-auth"];
+const CONN: &[&str] = &["loading", "stale"];
+const CONN_FAST: &[&str] = &["loading", "stale", "fast"];
+const FAST: &[&str] = &["fast"];
+// 只删除或修改元数据、不会占用更多内存的写命令，不需要 denyoom
+const MUTATE: &[&str] = &["write"];
+const MUTATE_FAST: &[&str] = &["write", "fast"];
+const PUBSUB: &[&str] = &["pubsub", "noscript", "loading", "stale"];
+const PUBSUB_FAST: &[&str] = &["pubsub", "loading", "stale", "fast"];
+const READ: &[&str] = &["readonly"];
+const READ_FAST: &[&str] = &["readonly", "fast"];
+const RESET: &[&str] = &["noscript", "loading", "stale", "fast"];
+const WRITE: &[&str] = &["write", "denyoom"];
+const WRITE_FAST: &[&str] = &["write", "denyoom", "fast"];
+
+/// 所有已实现命令的元数据，按命令名索引
+#[rustfmt::skip]
+static COMMAND_TABLE: LazyLock<HashMap<&'static str, CommandMeta>> = LazyLock::new(|| {
+    [
+        meta("acl",          -2, ADMIN,       0,  0, 0, &["admin", "slow", "dangerous"]),
+        meta("append",        3, WRITE,       1,  1, 1, &["write", "string", "fast"]),
+        meta("auth",         -2, AUTH,        0,  0, 0, &["fast", "connection"]),
+        meta("bitcount",     -2, READ,        1,  1, 1, &["read", "bitmap", "slow"]),
+        meta("client",       -2, ADMIN,       0,  0, 0, &["admin", "slow", "dangerous", "connection"]),
+        meta("cluster",      -2, CONN,        0,  0, 0, &["slow"]),
+        meta("command",      -1, CONN,        0,  0, 0, &["slow", "connection"]),
+        meta("dbsize",        1, READ_FAST,   0,  0, 0, &["keyspace", "read", "fast"]),
+        meta("debug",        -2, ADMIN,       0,  0, 0, &["admin", "slow", "dangerous"]),
+        meta("decr",          2, WRITE_FAST,  1,  1, 1, &["write", "string", "fast"]),
+        meta("del",          -2, MUTATE,      1, -1, 1, &["keyspace", "write", "slow"]),
+        meta("echo",          2, FAST,        0,  0, 0, &["fast", "connection"]),
+        meta("exists",       -2, READ_FAST,   1, -1, 1, &["keyspace", "read", "fast"]),
+        meta("expire",        3, MUTATE_FAST, 1,  1, 1, &["keyspace", "write", "fast"]),
+        meta("flushdb",       1, MUTATE,      0,  0, 0, &["keyspace", "write", "slow", "dangerous"]),
+        meta("get",           2, READ_FAST,   1,  1, 1, &["read", "string", "fast"]),
+        meta("getdel",        2, MUTATE_FAST, 1,  1, 1, &["write", "string", "fast"]),
+        meta("getrange",      4, READ,        1,  1, 1, &["read", "string", "slow"]),
+        meta("getset",        3, WRITE_FAST,  1,  1, 1, &["write", "string", "fast"]),
+        meta("hdel",         -3, MUTATE_FAST, 1,  1, 1, &["write", "hash", "fast"]),
+        meta("hello",        -1, RESET,       0,  0, 0, &["fast", "connection"]),
+        meta("hget",          3, READ_FAST,   1,  1, 1, &["read", "hash", "fast"]),
+        meta("hgetall",       2, READ,        1,  1, 1, &["read", "hash", "slow"]),
+        meta("hset",         -4, WRITE_FAST,  1,  1, 1, &["write", "hash", "fast"]),
+        meta("incr",          2, WRITE_FAST,  1,  1, 1, &["write", "string", "fast"]),
+        meta("incrbyfloat",   3, WRITE_FAST,  1,  1, 1, &["write", "string", "fast"]),
+        meta("info",         -1, CONN,        0,  0, 0, &["slow", "dangerous"]),
+        meta("keys",          2, READ,        0,  0, 0, &["keyspace", "read", "slow", "dangerous"]),
+        meta("lindex",        3, READ,        1,  1, 1, &["read", "list", "slow"]),
+        meta("llen",          2, READ_FAST,   1,  1, 1, &["read", "list", "fast"]),
+        meta("lpop",         -2, MUTATE_FAST, 1,  1, 1, &["write", "list", "fast"]),
+        meta("lpush",        -3, WRITE_FAST,  1,  1, 1, &["write", "list", "fast"]),
+        meta("lrange",        4, READ,        1,  1, 1, &["read", "list", "slow"]),
+        meta("mget",         -2, READ_FAST,   1, -1, 1, &["read", "string", "fast"]),
+        meta("move",          3, MUTATE_FAST, 1,  1, 1, &["keyspace", "write", "fast"]),
+        meta("mset",         -3, WRITE,       1, -1, 2, &["write", "string", "slow"]),
+        meta("msetnx",       -3, WRITE,       1, -1, 2, &["write", "string", "slow"]),
+        meta("object",       -2, READ,        2,  2, 1, &["keyspace", "read", "slow"]),
+        meta("persist",       2, MUTATE_FAST, 1,  1, 1, &["keyspace", "write", "fast"]),
+        meta("ping",         -1, FAST,        0,  0, 0, &["fast", "connection"]),
+        meta("psetex",        4, WRITE,       1,  1, 1, &["write", "string", "slow"]),
+        meta("psubscribe",   -2, PUBSUB,      0,  0, 0, &["pubsub", "slow"]),
+        meta("publish",       3, PUBSUB_FAST, 0,  0, 0, &["pubsub", "fast"]),
+        meta("pubsub",       -2, PUBSUB_FAST, 0,  0, 0, &["pubsub", "slow"]),
+        meta("punsubscribe", -1, PUBSUB,      0,  0, 0, &["pubsub", "slow"]),
+        meta("quit",         -1, AUTH,        0,  0, 0, &["fast", "connection"]),
+        meta("rename",        3, MUTATE,      1,  2, 1, &["keyspace", "write", "slow"]),
+        meta("renamenx",      3, MUTATE_FAST, 1,  2, 1, &["keyspace", "write", "fast"]),
+        meta("reset",         1, RESET,       0,  0, 0, &["fast", "connection"]),
+        meta("rpop",         -2, MUTATE_FAST, 1,  1, 1, &["write", "list", "fast"]),
+        meta("rpush",        -3, WRITE_FAST,  1,  1, 1, &["write", "list", "fast"]),
+        meta("sadd",         -3, WRITE_FAST,  1,  1, 1, &["write", "set", "fast"]),
+        meta("scan",         -2, READ,        0,  0, 0, &["keyspace", "read", "slow"]),
+        meta("select",        2, CONN_FAST,   0,  0, 0, &["fast", "connection"]),
+        meta("set",          -3, WRITE,       1,  1, 1, &["write", "string", "slow"]),
+        meta("setex",         4, WRITE,       1,  1, 1, &["write", "string", "slow"]),
+        meta("setnx",         3, WRITE_FAST,  1,  1, 1, &["write", "string", "fast"]),
+        meta("sismember",     3, READ_FAST,   1,  1, 1, &["read", "set", "fast"]),
+        meta("smembers",      2, READ,        1,  1, 1, &["read", "set", "slow"]),
+        meta("srem",         -3, MUTATE_FAST, 1,  1, 1, &["write", "set", "fast"]),
+        meta("strlen",        2, READ_FAST,   1,  1, 1, &["read", "string", "fast"]),
+        meta("substr",        4, READ,        1,  1, 1, &["read", "string", "slow"]),
+        meta("subscribe",    -2, PUBSUB,      0,  0, 0, &["pubsub", "slow"]),
+        meta("swapdb",        3, MUTATE_FAST, 0,  0, 0, &["keyspace", "write", "fast", "dangerous"]),
+        meta("ttl",           2, READ_FAST,   1,  1, 1, &["keyspace", "read", "fast"]),
+        meta("type",          2, READ_FAST,   1,  1, 1, &["keyspace", "read", "fast"]),
+        meta("unsubscribe",  -1, PUBSUB,      0,  0, 0, &["pubsub", "slow"]),
+    ]
+    .into_iter()
+    .map(|meta| (meta.name, meta))
+    .collect()
+});
+
+/// 命令别名表，键为别名，值为实际执行的命令名
+static COMMAND_ALIASES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
+    let mut aliases = HashMap::new();
+    // `SUBSTR` 是 Redis 1.x 中 `GETRANGE` 的旧名称，已废弃，仅为兼容旧客户端而保留
+    register_alias(&mut aliases, "substr", "getrange");
+    aliases
+});
+
+/// 注册一个命令别名，别名指向的命令必须已经在命令表中
+fn register_alias(
+    aliases: &mut HashMap<&'static str, &'static str>,
+    from: &'static str,
+    to: &'static str,
+) {
+    debug_assert!(COMMAND_TABLE.contains_key(to), "unknown command `{}`", to);
+    aliases.insert(from, to);
+}
+
+/// 将命令别名解析为实际的命令名，不是别名时原样返回
+pub(crate) fn resolve_alias(name: &str) -> &str {
+    COMMAND_ALIASES.get(name).copied().unwrap_or(name)
+}
+
+/// 返回属于指定 ACL 分类的所有命令名，按名称排序
+pub(crate) fn commands_in_category(category: &str) -> Vec<&'static str> {
+    let mut names: Vec<_> = COMMAND_TABLE
+        .values()
+        .filter(|meta| meta.categories.contains(&category))
+        .map(|meta| meta.name)
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+/// `COMMAND` 的完整响应只依赖静态的命令表，构建一次后缓存起来
+static COMMAND_ALL: LazyLock<Frame> = LazyLock::new(|| {
+    let mut metas: Vec<_> = COMMAND_TABLE.values().collect();
+    metas.sort_by_key(|meta| meta.name);
+    Frame::Array(metas.into_iter().map(CommandMeta::info_frame).collect())
+});
+
+impl CommandMeta {
+    fn info_frame(&self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from_static(self.name.as_bytes()));
+        frame.push_int(self.arity);
+        frame.push_frame(Frame::Array(
+            self.flags
+                .iter()
+                .map(|flag| Frame::Simple(flag.to_string()))
+                .collect(),
+        ));
+        frame.push_int(self.first_key);
+        frame.push_int(self.last_key);
+        frame.push_int(self.step);
+        frame.push_frame(Frame::Array(
+            self.categories
+                .iter()
+                .map(|category| Frame::Simple(format!("@{}", category)))
+                .collect(),
+        ));
+        frame
+    }
+}
+
+impl CommandCmd {
+    pub(crate) fn all() -> CommandCmd {
+        CommandCmd {
+            subcommand: CommandSubcommand::All,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<CommandCmd> {
+        use ParseError::EndOfStream;
+
+        let subcommand = match parse.next_string() {
+            Ok(s) => s.to_lowercase(),
+            Err(EndOfStream) => return Ok(CommandCmd::all()),
+            Err(e) => return Err(e.into()),
+        };
+        let subcommand = match &subcommand[..] {
+            "info" => {
+                let mut names = vec![];
+                loop {
+                    match parse.next_string() {
+                        Ok(name) => names.push(name),
+                        Err(EndOfStream) => break,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                // 不带命令名的 `COMMAND INFO` 与 `COMMAND` 等价
+                if names.is_empty() {
+                    CommandSubcommand::All
+                } else {
+                    CommandSubcommand::Info { names }
+                }
+            }
+            // 参数错误在 `apply` 中回复给客户端，避免一次错误的查询断开连接
+            "getkeys" => CommandSubcommand::GetKeys {
+                args: parse.remaining(),
+            },
+            _ => {
+                parse.remaining();
+                CommandSubcommand::Unknown { name: subcommand }
+            }
+        };
+        Ok(CommandCmd { subcommand })
+    }
+
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            CommandSubcommand::All => {
+                debug!("responding with cached command table");
+                dst.write_frame(&COMMAND_ALL).await?;
+                return Ok(());
+            }
+            CommandSubcommand::Info { names } => Frame::Array(
+                names
+                    .iter()
+                    .map(|name| match COMMAND_TABLE.get(&name.to_lowercase()[..]) {
+                        Some(meta) => meta.info_frame(),
+                        None => Frame::Null,
+                    })
+                    .collect(),
+            ),
+            CommandSubcommand::GetKeys { args } if args.is_empty() => Frame::Error(
+                "ERR wrong number of arguments for 'command|getkeys' command".to_string(),
+            ),
+            CommandSubcommand::GetKeys { args } => match Command::get_keys(Frame::Array(args)) {
+                Ok(keys) if keys.is_empty() => {
+                    Frame::Error("ERR The command has no key arguments".to_string())
+                }
+                Ok(keys) => Frame::Array(
+                    keys.into_iter()
+                        .map(|key| Frame::Bulk(Bytes::from(key)))
+                        .collect(),
+                ),
+                Err(err) => Frame::Error(format!("ERR {}", err)),
+            },
+            CommandSubcommand::Unknown { name } => Frame::Error(format!(
+                "ERR unknown subcommand '{}'. Try COMMAND HELP.",
+                name
+            )),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("command".as_bytes()));
+        match self.subcommand {
+            CommandSubcommand::All => {}
+            CommandSubcommand::Info { names } => {
+                frame.push_bulk(Bytes::from("info".as_bytes()));
+                for name in names {
+                    frame.push_bulk(Bytes::from(name.into_bytes()));
+                }
+            }
+            CommandSubcommand::GetKeys { args } => {
+                frame.push_bulk(Bytes::from("getkeys".as_bytes()));
+                for arg in args {
+                    frame.push_frame(arg);
+                }
+            }
+            CommandSubcommand::Unknown { name } => {
+                frame.push_bulk(Bytes::from(name.into_bytes()));
+            }
+        }
+        frame
+    }
+}
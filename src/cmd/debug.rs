@@ -0,0 +1,49 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct DebugCmd {
+    subcommand: DebugSubcommand,
+}
+
+#[derive(Debug)]
+pub enum DebugSubcommand {
+    Object { key: String },
+}
+
+impl DebugCmd {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<DebugCmd> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        let subcommand = match &subcommand[..] {
+            "object" => DebugSubcommand::Object {
+                key: parse.next_string()?,
+            },
+            _ => return Err(format!("unknown `debug` subcommand '{}'", subcommand).into()),
+        };
+        Ok(DebugCmd { subcommand })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            DebugSubcommand::Object { key } => match db.debug_object(&key) {
+                Some(info) => Frame::Simple(format!(
+                    "Value at:{:#x} refcount:{} encoding:{} serializedlength:{} lru:{} lru_seconds_idle:{}",
+                    info.addr,
+                    info.refcount,
+                    info.encoding,
+                    info.serialized_len,
+                    info.lru,
+                    info.lru_idle
+                )),
+                None => Frame::Error("ERR no such key".to_string()),
+            },
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::connection::Connection;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Echo {
+    msg: Bytes,
+}
+
+impl Echo {
+    pub fn new(msg: Bytes) -> Echo {
+        Echo { msg }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Echo> {
+        let msg = parse.next_bytes()?;
+        // 只接受一个参数
+        parse.finish()?;
+        Ok(Echo { msg })
+    }
+
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        let response = Frame::Bulk(self.msg);
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("echo".as_bytes()));
+        frame.push_bulk(self.msg);
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::Command;
+
+    #[tokio::test]
+    async fn echo_round_trips_binary_bytes() {
+        let (client, server) = tokio::io::duplex(4096);
+        let mut client: Connection = Connection::new(Box::new(client));
+        let mut server: Connection = Connection::new(Box::new(server));
+        let msg = Bytes::from_static(&[0xFF, 0x00, 0xFE, b'\r', b'\n']);
+
+        let echo = match Command::from_frame(Echo::new(msg.clone()).into_frame()).unwrap() {
+            Command::Echo(echo) => echo,
+            command => panic!("expected echo, got {:?}", command),
+        };
+        echo.apply(&mut server).await.unwrap();
+
+        match client.read_frame().await.unwrap() {
+            Some(Frame::Bulk(data)) => assert_eq!(data, msg),
+            frame => panic!("expected bulk, got {:?}", frame),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Exists {
+    keys: Vec<String>,
+}
+
+impl Exists {
+    pub fn new(keys: Vec<String>) -> Exists {
+        Exists { keys }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Exists> {
+        use ParseError::EndOfStream;
+
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Exists { keys })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = Frame::Integer(db.exists(&self.keys) as i64);
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("exists".as_bytes()));
+        for key in self.keys {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+        }
+        frame
+    }
+}
+
+impl KeyExtractor for Exists {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(Exists::parse_frames(parse)?.keys)
+    }
+}
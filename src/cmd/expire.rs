@@ -0,0 +1,57 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use std::time::Duration;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    seconds: i64,
+}
+
+impl Expire {
+    pub fn new(key: impl ToString, seconds: i64) -> Expire {
+        Expire {
+            key: key.to_string(),
+            seconds,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Expire> {
+        let key = parse.next_string()?;
+        let seconds = parse.next_signed_int()?;
+        Ok(Expire { key, seconds })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        // 与 Redis 一致，非正数的过期时间会立即删除该键
+        let existed = if self.seconds <= 0 {
+            db.del(&[self.key]) > 0
+        } else {
+            db.expire(&self.key, Duration::from_secs(self.seconds as u64))
+        };
+        let response = Frame::Integer(existed as i64);
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("expire".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.seconds.to_string()));
+        frame
+    }
+}
+
+impl KeyExtractor for Expire {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
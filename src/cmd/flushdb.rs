@@ -0,0 +1,34 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug, Default)]
+pub struct FlushDb;
+
+impl FlushDb {
+    pub fn new() -> FlushDb {
+        FlushDb
+    }
+
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> crate::Result<FlushDb> {
+        Ok(FlushDb)
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        db.flush();
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("flushdb".as_bytes()));
+        frame
+    }
+}
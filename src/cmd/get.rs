@@ -1,3 +1,4 @@
+use crate::cmd::KeyExtractor;
 use crate::connection::Connection;
 use crate::db::Db;
 use crate::frame::Frame;
@@ -27,10 +28,10 @@ impl Get {
     }
 
     pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
-        let response = if let Some(value) = db.get(&self.key) {
-            Frame::Bulk(value)
-        } else {
-            Frame::Null
+        let response = match db.get(&self.key, !dst.state.no_touch) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
         };
         debug!(?response);
 
@@ -45,3 +46,9 @@ impl Get {
         frame
     }
 }
+
+impl KeyExtractor for Get {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
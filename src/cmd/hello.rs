@@ -0,0 +1,70 @@
+use crate::connection::{Connection, Protocol};
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug, Default)]
+pub struct Hello {
+    protover: Option<u64>,
+}
+
+impl Hello {
+    pub fn new(protover: Option<u64>) -> Hello {
+        Hello { protover }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Hello> {
+        let protover = match parse.next_int() {
+            Ok(protover) => Some(protover),
+            Err(ParseError::EndOfStream) => None,
+            Err(e) => return Err(e.into()),
+        };
+        parse.finish()?;
+        Ok(Hello { protover })
+    }
+
+    /// 切换连接的协议版本并返回服务器信息。
+    /// 协议版本先于回复生效，因此回复本身已按新协议编码；不带参数时只返回信息
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        let protocol = match self.protover {
+            None => dst.state.protocol,
+            Some(2) => Protocol::Resp2,
+            Some(3) => Protocol::Resp3,
+            Some(_) => {
+                let response = Frame::Error("NOPROTO unsupported protocol version".to_string());
+                debug!(?response);
+                dst.write_frame(&response).await?;
+                return Ok(());
+            }
+        };
+        dst.state.protocol = protocol;
+
+        let proto = match protocol {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        };
+        let field = |name: &'static str| Frame::Bulk(Bytes::from_static(name.as_bytes()));
+        let response = Frame::Map(vec![
+            (field("server"), field("redis")),
+            (field("version"), field(env!("CARGO_PKG_VERSION"))),
+            (field("proto"), Frame::Integer(proto)),
+            (field("mode"), field("standalone")),
+            (field("role"), field("master")),
+            (field("modules"), Frame::array()),
+        ]);
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hello".as_bytes()));
+        if let Some(protover) = self.protover {
+            frame.push_bulk(Bytes::from(protover.to_string().into_bytes()));
+        }
+        frame
+    }
+}
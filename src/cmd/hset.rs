@@ -0,0 +1,74 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct HSet {
+    key: String,
+    pairs: Vec<(Bytes, Bytes)>,
+}
+
+impl HSet {
+    pub fn new(key: impl ToString, pairs: Vec<(Bytes, Bytes)>) -> HSet {
+        HSet {
+            key: key.to_string(),
+            pairs,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<HSet> {
+        use ParseError::EndOfStream;
+
+        let key = parse.next_string()?;
+        let mut pairs = vec![(parse.next_bytes()?, parse.next_bytes()?)];
+
+        loop {
+            let field = match parse.next_bytes() {
+                Ok(field) => field,
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            };
+            let value = match parse.next_bytes() {
+                Ok(value) => value,
+                Err(EndOfStream) => {
+                    return Err("ERR wrong number of arguments for 'hset' command".into());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            pairs.push((field, value));
+        }
+        Ok(HSet { key, pairs })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.hset(&self.key, self.pairs) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("hset".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        for (field, value) in self.pairs {
+            frame.push_bulk(field);
+            frame.push_bulk(value);
+        }
+        frame
+    }
+}
+
+impl KeyExtractor for HSet {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
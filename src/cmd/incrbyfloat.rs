@@ -0,0 +1,54 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: String,
+    delta: f64,
+}
+
+impl IncrByFloat {
+    pub fn new(key: impl ToString, delta: f64) -> IncrByFloat {
+        IncrByFloat {
+            key: key.to_string(),
+            delta,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<IncrByFloat> {
+        let key = parse.next_string()?;
+        let delta = parse.next_float()?;
+        Ok(IncrByFloat { key, delta })
+    }
+
+    /// 新值按批量字符串返回，与 Redis 一致在两种协议下都不使用浮点数帧
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.incr_by_float(&self.key, self.delta) {
+            Ok(value) => Frame::Bulk(Bytes::from(Frame::format_double(value))),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("incrbyfloat".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(Frame::format_double(self.delta)));
+        frame
+    }
+}
+
+impl KeyExtractor for IncrByFloat {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
@@ -0,0 +1,131 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use std::fmt::Write;
+use tracing::debug;
+
+#[derive(Debug, Default)]
+pub struct Info {
+    sections: Vec<String>,
+}
+
+/// 输出某个分组内容的函数
+type SectionWriter = fn(&mut String, &Db);
+
+/// 支持的信息分组：分组名、不指定分组时是否默认输出，以及输出该分组的函数
+const SECTIONS: &[(&str, bool, SectionWriter)] = &[
+    ("server", true, server),
+    ("clients", true, clients),
+    ("stats", true, stats),
+    ("commandstats", false, commandstats),
+    ("keyspace", true, keyspace),
+];
+
+impl Info {
+    pub fn new(sections: Vec<String>) -> Info {
+        Info { sections }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Info> {
+        use ParseError::EndOfStream;
+
+        let mut sections = vec![];
+        loop {
+            match parse.next_string() {
+                Ok(section) => sections.push(section.to_lowercase()),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Info { sections })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let mut info = String::new();
+        for &(section, default, write_section) in SECTIONS {
+            let selected =
+                if self.sections.is_empty() || self.sections.iter().any(|s| s == "default") {
+                    default
+                } else {
+                    self.sections
+                        .iter()
+                        .any(|s| s == section || s == "all" || s == "everything")
+                };
+            if !selected {
+                continue;
+            }
+            if !info.is_empty() {
+                info.push_str("\r\n");
+            }
+            write_section(&mut info, db);
+        }
+
+        let response = Frame::Bulk(Bytes::from(info));
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("info".as_bytes()));
+        for section in self.sections {
+            frame.push_bulk(Bytes::from(section.into_bytes()));
+        }
+        frame
+    }
+}
+
+// 每个分组按 `# Section` 标题加 `key:value` 行的格式输出
+
+fn server(info: &mut String, db: &Db) {
+    let uptime = db.uptime().as_secs();
+    info.push_str("# Server\r\n");
+    let _ = write!(info, "my_redis_version:{}\r\n", env!("CARGO_PKG_VERSION"));
+    let _ = write!(info, "process_id:{}\r\n", std::process::id());
+    let _ = write!(info, "uptime_in_seconds:{}\r\n", uptime);
+    let _ = write!(info, "uptime_in_days:{}\r\n", uptime / (24 * 60 * 60));
+}
+
+fn clients(info: &mut String, db: &Db) {
+    info.push_str("# Clients\r\n");
+    let _ = write!(info, "connected_clients:{}\r\n", db.connected_clients());
+}
+
+fn stats(info: &mut String, db: &Db) {
+    info.push_str("# Stats\r\n");
+    let _ = write!(
+        info,
+        "pubsub_channels:{}\r\n",
+        db.pubsub_channels(None).len()
+    );
+    let _ = write!(info, "pubsub_patterns:{}\r\n", db.pubsub_patterns());
+}
+
+fn keyspace(info: &mut String, db: &Db) {
+    info.push_str("# Keyspace\r\n");
+    // 与 Redis 一致，只输出非空的数据库
+    for index in 0..db.databases() {
+        let Some(db) = db.select(index) else { break };
+        let keys = db.dbsize();
+        if keys > 0 {
+            let _ = write!(
+                info,
+                "db{}:keys={},expires={}\r\n",
+                index,
+                keys,
+                db.expires()
+            );
+        }
+    }
+}
+
+fn commandstats(info: &mut String, db: &Db) {
+    info.push_str("# Commandstats\r\n");
+    for (name, calls) in db.command_stats() {
+        let _ = write!(info, "cmdstat_{}:calls={}\r\n", name, calls);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct LPop {
+    key: String,
+    count: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    key: String,
+    count: Option<u64>,
+}
+
+impl LPop {
+    pub fn new(key: impl ToString, count: Option<u64>) -> LPop {
+        LPop {
+            key: key.to_string(),
+            count,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<LPop> {
+        let (key, count) = parse_pop(parse)?;
+        Ok(LPop { key, count })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        apply_pop(db, dst, &self.key, self.count, true).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        pop_frame("lpop", self.key, self.count)
+    }
+}
+
+impl RPop {
+    pub fn new(key: impl ToString, count: Option<u64>) -> RPop {
+        RPop {
+            key: key.to_string(),
+            count,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<RPop> {
+        let (key, count) = parse_pop(parse)?;
+        Ok(RPop { key, count })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        apply_pop(db, dst, &self.key, self.count, false).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        pop_frame("rpop", self.key, self.count)
+    }
+}
+
+/// 解析键和可选的元素数量
+fn parse_pop(parse: &mut Parse) -> crate::Result<(String, Option<u64>)> {
+    let key = parse.next_string()?;
+    let count = match parse.next_int() {
+        Ok(count) => Some(count),
+        Err(ParseError::EndOfStream) => None,
+        Err(e) => return Err(e.into()),
+    };
+    Ok((key, count))
+}
+
+/// 从列表的头部或尾部弹出元素。不带数量时返回单个元素，带数量时返回数组；键不存在时返回空值
+async fn apply_pop(
+    db: &Db,
+    dst: &mut Connection,
+    key: &str,
+    count: Option<u64>,
+    front: bool,
+) -> crate::Result<()> {
+    let response = match db.pop(key, count.unwrap_or(1) as usize, front) {
+        Ok(None) => Frame::Null,
+        Ok(Some(values)) if count.is_some() => {
+            Frame::Array(values.into_iter().map(Frame::Bulk).collect())
+        }
+        Ok(Some(values)) => values.into_iter().next().map_or(Frame::Null, Frame::Bulk),
+        Err(err) => Frame::Error(err.to_string()),
+    };
+    debug!(?response);
+
+    dst.write_frame(&response).await?;
+    Ok(())
+}
+
+fn pop_frame(name: &str, key: String, count: Option<u64>) -> Frame {
+    let mut frame = Frame::array();
+    frame.push_bulk(Bytes::from(name.to_string()));
+    frame.push_bulk(Bytes::from(key.into_bytes()));
+    if let Some(count) = count {
+        frame.push_bulk(Bytes::from(count.to_string()));
+    }
+    frame
+}
+
+impl KeyExtractor for LPop {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
+
+impl KeyExtractor for RPop {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
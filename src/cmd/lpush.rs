@@ -0,0 +1,120 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct LPush {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+impl LPush {
+    pub fn new(key: impl ToString, values: Vec<Bytes>) -> LPush {
+        LPush {
+            key: key.to_string(),
+            values,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<LPush> {
+        let (key, values) = parse_push(parse)?;
+        Ok(LPush { key, values })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        apply_push(db, dst, &self.key, self.values, true).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        push_frame("lpush", self.key, self.values)
+    }
+}
+
+impl RPush {
+    pub fn new(key: impl ToString, values: Vec<Bytes>) -> RPush {
+        RPush {
+            key: key.to_string(),
+            values,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<RPush> {
+        let (key, values) = parse_push(parse)?;
+        Ok(RPush { key, values })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        apply_push(db, dst, &self.key, self.values, false).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        push_frame("rpush", self.key, self.values)
+    }
+}
+
+/// 解析键和至少一个元素
+fn parse_push(parse: &mut Parse) -> crate::Result<(String, Vec<Bytes>)> {
+    use ParseError::EndOfStream;
+
+    let key = parse.next_string()?;
+    let mut values = vec![parse.next_bytes()?];
+
+    loop {
+        match parse.next_bytes() {
+            Ok(value) => values.push(value),
+            Err(EndOfStream) => break,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok((key, values))
+}
+
+/// 将元素插入到列表的头部或尾部并返回列表的新长度，键不是列表时返回错误帧
+async fn apply_push(
+    db: &Db,
+    dst: &mut Connection,
+    key: &str,
+    values: Vec<Bytes>,
+    front: bool,
+) -> crate::Result<()> {
+    let response = match db.push(key, values, front) {
+        Ok(len) => Frame::Integer(len as i64),
+        Err(err) => Frame::Error(err.to_string()),
+    };
+    debug!(?response);
+
+    dst.write_frame(&response).await?;
+    Ok(())
+}
+
+fn push_frame(name: &str, key: String, values: Vec<Bytes>) -> Frame {
+    let mut frame = Frame::array();
+    frame.push_bulk(Bytes::from(name.to_string()));
+    frame.push_bulk(Bytes::from(key.into_bytes()));
+    for value in values {
+        frame.push_bulk(value);
+    }
+    frame
+}
+
+impl KeyExtractor for LPush {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
+
+impl KeyExtractor for RPush {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
@@ -0,0 +1,63 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct MGet {
+    keys: Vec<String>,
+}
+
+impl MGet {
+    pub fn new(keys: Vec<String>) -> MGet {
+        MGet { keys }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<MGet> {
+        use ParseError::EndOfStream;
+
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(MGet { keys })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let values = db.mget(&self.keys, !dst.state.no_touch);
+        // 按照请求中键的顺序返回，不存在的键对应空值
+        let response = Frame::Array(
+            values
+                .into_iter()
+                .map(|value| value.map(Frame::Bulk).unwrap_or(Frame::Null))
+                .collect(),
+        );
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("mget".as_bytes()));
+        for key in self.keys {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+        }
+        frame
+    }
+}
+
+impl KeyExtractor for MGet {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(MGet::parse_frames(parse)?.keys)
+    }
+}
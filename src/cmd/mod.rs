@@ -1,7 +1,62 @@
+mod acl;
+mod append;
+mod auth;
+mod bitcount;
+mod client_no_evict;
+mod client_no_touch;
+mod cluster;
+pub(crate) mod command;
+mod dbsize;
+mod debug;
+mod del;
+mod echo;
+mod exists;
+mod expire;
+mod flushdb;
 mod get;
+mod getdel;
+mod getrange;
+mod getset;
+mod hdel;
+mod hello;
+mod hget;
+mod hgetall;
+mod hset;
+mod incr;
+mod incrbyfloat;
+mod info;
+mod keys;
+mod lindex;
+mod llen;
+mod lpop;
+mod lpush;
+mod lrange;
+mod mget;
+mod move_key;
+mod mset;
+mod msetnx;
+mod object;
+mod persist;
+mod ping;
 mod publish;
+mod pubsub;
+mod quit;
+mod rename;
+mod reset;
+mod sadd;
+mod scan;
+mod select;
 mod set;
+mod setex;
+mod setnx;
+mod sismember;
+mod smembers;
+mod srem;
+mod strlen;
 mod subscribe;
+mod swapdb;
+mod ttl;
+mod type_of;
 mod unknown;
 
 use crate::connection::Connection;
@@ -9,19 +64,149 @@ use crate::db::Db;
 use crate::frame::Frame;
 use crate::parse::Parse;
 use crate::shutdown::Shutdown;
+pub use acl::Acl;
+pub use append::Append;
+pub use auth::Auth;
+pub use bitcount::{BitCount, BitCountUnit};
+pub use client_no_evict::ClientNoEvict;
+pub use client_no_touch::ClientNoTouch;
+pub use cluster::Cluster;
+pub use command::CommandCmd;
+pub use dbsize::DbSize;
+pub use debug::DebugCmd;
+pub use del::Del;
+pub use echo::Echo;
+pub use exists::Exists;
+pub use expire::Expire;
+pub use flushdb::FlushDb;
 pub use get::Get;
+pub use getdel::GetDel;
+pub use getrange::GetRange;
+pub use getset::GetSet;
+pub use hdel::HDel;
+pub use hello::Hello;
+pub use hget::HGet;
+pub use hgetall::HGetAll;
+pub use hset::HSet;
+pub use incr::Decr;
+pub use incr::Incr;
+pub use incrbyfloat::IncrByFloat;
+pub use info::Info;
+pub use keys::Keys;
+pub use lindex::LIndex;
+pub use llen::LLen;
+pub use lpop::LPop;
+pub use lpop::RPop;
+pub use lpush::LPush;
+pub use lpush::RPush;
+pub use lrange::LRange;
+pub use mget::MGet;
+pub use move_key::Move;
+pub use mset::MSet;
+pub use msetnx::MSetNx;
+pub use object::Object;
+pub use persist::Persist;
+pub use ping::Ping;
 pub use publish::Publish;
-pub use set::Set;
+pub use pubsub::PubSub;
+pub use quit::Quit;
+pub use rename::Rename;
+pub use rename::RenameNx;
+pub use reset::Reset;
+pub use sadd::SAdd;
+pub use scan::Scan;
+pub use select::Select;
+pub use set::{Set, SetCondition};
+pub use setex::PSetEx;
+pub use setex::SetEx;
+pub use setnx::SetNx;
+pub use sismember::SIsMember;
+pub use smembers::SMembers;
+pub use srem::SRem;
+pub use strlen::Strlen;
+pub(crate) use subscribe::LAGGED_PREFIX;
+pub use subscribe::PSubscribe;
+pub use subscribe::PUnsubscribe;
 pub use subscribe::Subscribe;
 pub use subscribe::Unsubscribe;
+pub use swapdb::SwapDb;
+pub use ttl::Ttl;
+pub use type_of::Type;
 pub use unknown::Unknown;
 
+/// 在不执行命令的情况下，从命令参数中提取键名
+pub(crate) trait KeyExtractor {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>>;
+}
+
 #[derive(Debug)]
 pub enum Command {
+    Acl(Acl),
+    Append(Append),
+    Auth(Auth),
+    BitCount(BitCount),
+    ClientNoEvict(ClientNoEvict),
+    ClientNoTouch(ClientNoTouch),
+    Cluster(Cluster),
+    CommandCmd(CommandCmd),
+    DbSize(DbSize),
+    Debug(DebugCmd),
+    Decr(Decr),
+    Del(Del),
+    Echo(Echo),
+    Exists(Exists),
+    Expire(Expire),
+    FlushDb(FlushDb),
     Get(Get),
+    GetDel(GetDel),
+    GetRange(GetRange),
+    GetSet(GetSet),
+    HDel(HDel),
+    HGet(HGet),
+    HGetAll(HGetAll),
+    HSet(HSet),
+    Hello(Hello),
+    Incr(Incr),
+    IncrByFloat(IncrByFloat),
+    Info(Info),
+    Keys(Keys),
+    LIndex(LIndex),
+    LLen(LLen),
+    LPop(LPop),
+    LPush(LPush),
+    LRange(LRange),
+    MGet(MGet),
+    MSet(MSet),
+    MSetNx(MSetNx),
+    Move(Move),
+    Object(Object),
+    PSetEx(PSetEx),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Persist(Persist),
+    Ping(Ping),
+    PubSub(PubSub),
     Publish(Publish),
+    Quit(Quit),
+    RPop(RPop),
+    RPush(RPush),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    Reset(Reset),
+    SAdd(SAdd),
+    SIsMember(SIsMember),
+    SMembers(SMembers),
+    SRem(SRem),
+    Scan(Scan),
+    Select(Select),
     Set(Set),
+    SetEx(SetEx),
+    SetNx(SetNx),
+    Strlen(Strlen),
     Subscribe(Subscribe),
+    SwapDb(SwapDb),
+    Ttl(Ttl),
+    Type(Type),
     Unsubscribe(Unsubscribe),
     Unknown(Unknown),
 }
@@ -30,11 +215,73 @@ impl Command {
     pub fn from_frame(frame: Frame) -> crate::Result<Command> {
         let mut parse = Parse::new(frame)?;
         let command_name = parse.next_string()?.to_lowercase();
+        let command_name = command::resolve_alias(&command_name).to_string();
         let command = match &command_name[..] {
+            "acl" => Command::Acl(Acl::parse_frames(&mut parse)?),
+            "append" => Command::Append(Append::parse_frames(&mut parse)?),
+            "auth" => Command::Auth(Auth::parse_frames(&mut parse)?),
+            "bitcount" => Command::BitCount(BitCount::parse_frames(&mut parse)?),
+            "client" => Command::parse_client(&mut parse)?,
+            "cluster" => Command::Cluster(Cluster::parse_frames(&mut parse)?),
+            "command" => Command::CommandCmd(CommandCmd::parse_frames(&mut parse)?),
+            "dbsize" => Command::DbSize(DbSize::parse_frames(&mut parse)?),
+            "debug" => Command::Debug(DebugCmd::parse_frames(&mut parse)?),
+            "decr" => Command::Decr(Decr::parse_frames(&mut parse)?),
+            "del" => Command::Del(Del::parse_frames(&mut parse)?),
+            "echo" => Command::Echo(Echo::parse_frames(&mut parse)?),
+            "exists" => Command::Exists(Exists::parse_frames(&mut parse)?),
+            "expire" => Command::Expire(Expire::parse_frames(&mut parse)?),
+            "flushdb" => Command::FlushDb(FlushDb::parse_frames(&mut parse)?),
             "get" => Command::Get(Get::parse_frames(&mut parse)?),
+            "getdel" => Command::GetDel(GetDel::parse_frames(&mut parse)?),
+            "getrange" => Command::GetRange(GetRange::parse_frames(&mut parse)?),
+            "getset" => Command::GetSet(GetSet::parse_frames(&mut parse)?),
+            "hdel" => Command::HDel(HDel::parse_frames(&mut parse)?),
+            "hello" => Command::Hello(Hello::parse_frames(&mut parse)?),
+            "hget" => Command::HGet(HGet::parse_frames(&mut parse)?),
+            "hgetall" => Command::HGetAll(HGetAll::parse_frames(&mut parse)?),
+            "hset" => Command::HSet(HSet::parse_frames(&mut parse)?),
+            "incr" => Command::Incr(Incr::parse_frames(&mut parse)?),
+            "incrbyfloat" => Command::IncrByFloat(IncrByFloat::parse_frames(&mut parse)?),
+            "info" => Command::Info(Info::parse_frames(&mut parse)?),
+            "keys" => Command::Keys(Keys::parse_frames(&mut parse)?),
+            "lindex" => Command::LIndex(LIndex::parse_frames(&mut parse)?),
+            "llen" => Command::LLen(LLen::parse_frames(&mut parse)?),
+            "lpop" => Command::LPop(LPop::parse_frames(&mut parse)?),
+            "lpush" => Command::LPush(LPush::parse_frames(&mut parse)?),
+            "lrange" => Command::LRange(LRange::parse_frames(&mut parse)?),
+            "mget" => Command::MGet(MGet::parse_frames(&mut parse)?),
+            "move" => Command::Move(Move::parse_frames(&mut parse)?),
+            "mset" => Command::MSet(MSet::parse_frames(&mut parse)?),
+            "msetnx" => Command::MSetNx(MSetNx::parse_frames(&mut parse)?),
+            "object" => Command::Object(Object::parse_frames(&mut parse)?),
+            "persist" => Command::Persist(Persist::parse_frames(&mut parse)?),
+            "ping" => Command::Ping(Ping::parse_frames(&mut parse)?),
+            "psetex" => Command::PSetEx(PSetEx::parse_frames(&mut parse)?),
+            "psubscribe" => Command::PSubscribe(PSubscribe::parse_frames(&mut parse)?),
             "publish" => Command::Publish(Publish::parse_frames(&mut parse)?),
+            "pubsub" => Command::PubSub(PubSub::parse_frames(&mut parse)?),
+            "punsubscribe" => Command::PUnsubscribe(PUnsubscribe::parse_frames(&mut parse)?),
+            "quit" => Command::Quit(Quit::parse_frames(&mut parse)?),
+            "rename" => Command::Rename(Rename::parse_frames(&mut parse)?),
+            "renamenx" => Command::RenameNx(RenameNx::parse_frames(&mut parse)?),
+            "reset" => Command::Reset(Reset::parse_frames(&mut parse)?),
+            "rpop" => Command::RPop(RPop::parse_frames(&mut parse)?),
+            "rpush" => Command::RPush(RPush::parse_frames(&mut parse)?),
+            "sadd" => Command::SAdd(SAdd::parse_frames(&mut parse)?),
+            "scan" => Command::Scan(Scan::parse_frames(&mut parse)?),
+            "select" => Command::Select(Select::parse_frames(&mut parse)?),
             "set" => Command::Set(Set::parse_frames(&mut parse)?),
+            "setex" => Command::SetEx(SetEx::parse_frames(&mut parse)?),
+            "setnx" => Command::SetNx(SetNx::parse_frames(&mut parse)?),
+            "sismember" => Command::SIsMember(SIsMember::parse_frames(&mut parse)?),
+            "smembers" => Command::SMembers(SMembers::parse_frames(&mut parse)?),
+            "srem" => Command::SRem(SRem::parse_frames(&mut parse)?),
+            "strlen" => Command::Strlen(Strlen::parse_frames(&mut parse)?),
             "subscribe" => Command::Subscribe(Subscribe::parse_frames(&mut parse)?),
+            "swapdb" => Command::SwapDb(SwapDb::parse_frames(&mut parse)?),
+            "ttl" => Command::Ttl(Ttl::parse_frames(&mut parse)?),
+            "type" => Command::Type(Type::parse_frames(&mut parse)?),
             "unsubscribe" => Command::Unsubscribe(Unsubscribe::parse_frames(&mut parse)?),
             _ => return Ok(Command::Unknown(Unknown::new(command_name))),
         };
@@ -42,6 +289,70 @@ impl Command {
         Ok(command)
     }
 
+    /// 根据子命令名称解析 `CLIENT` 命令
+    fn parse_client(parse: &mut Parse) -> crate::Result<Command> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        let command = match &subcommand[..] {
+            "no-evict" => Command::ClientNoEvict(ClientNoEvict::parse_frames(parse)?),
+            "no-touch" => Command::ClientNoTouch(ClientNoTouch::parse_frames(parse)?),
+            _ => return Err(format!("unknown `client` subcommand '{}'", subcommand).into()),
+        };
+        Ok(command)
+    }
+
+    /// 解析 `frame` 中的命令并返回其中的键名，但不执行该命令
+    pub(crate) fn get_keys(frame: Frame) -> crate::Result<Vec<String>> {
+        let mut parse = Parse::new(frame)?;
+        let command_name = parse.next_string()?.to_lowercase();
+        let keys = match command::resolve_alias(&command_name) {
+            "append" => Append::extract_keys(&mut parse)?,
+            "bitcount" => BitCount::extract_keys(&mut parse)?,
+            "decr" => Decr::extract_keys(&mut parse)?,
+            "del" => Del::extract_keys(&mut parse)?,
+            "exists" => Exists::extract_keys(&mut parse)?,
+            "expire" => Expire::extract_keys(&mut parse)?,
+            "get" => Get::extract_keys(&mut parse)?,
+            "getdel" => GetDel::extract_keys(&mut parse)?,
+            "getrange" => GetRange::extract_keys(&mut parse)?,
+            "getset" => GetSet::extract_keys(&mut parse)?,
+            "hdel" => HDel::extract_keys(&mut parse)?,
+            "hget" => HGet::extract_keys(&mut parse)?,
+            "hgetall" => HGetAll::extract_keys(&mut parse)?,
+            "hset" => HSet::extract_keys(&mut parse)?,
+            "incr" => Incr::extract_keys(&mut parse)?,
+            "incrbyfloat" => IncrByFloat::extract_keys(&mut parse)?,
+            "lindex" => LIndex::extract_keys(&mut parse)?,
+            "llen" => LLen::extract_keys(&mut parse)?,
+            "lpop" => LPop::extract_keys(&mut parse)?,
+            "lpush" => LPush::extract_keys(&mut parse)?,
+            "lrange" => LRange::extract_keys(&mut parse)?,
+            "mget" => MGet::extract_keys(&mut parse)?,
+            "move" => Move::extract_keys(&mut parse)?,
+            "mset" => MSet::extract_keys(&mut parse)?,
+            "msetnx" => MSetNx::extract_keys(&mut parse)?,
+            "persist" => Persist::extract_keys(&mut parse)?,
+            "psetex" => PSetEx::extract_keys(&mut parse)?,
+            "publish" => Publish::extract_keys(&mut parse)?,
+            "rename" => Rename::extract_keys(&mut parse)?,
+            "renamenx" => RenameNx::extract_keys(&mut parse)?,
+            "rpop" => RPop::extract_keys(&mut parse)?,
+            "rpush" => RPush::extract_keys(&mut parse)?,
+            "sadd" => SAdd::extract_keys(&mut parse)?,
+            "set" => Set::extract_keys(&mut parse)?,
+            "setex" => SetEx::extract_keys(&mut parse)?,
+            "setnx" => SetNx::extract_keys(&mut parse)?,
+            "sismember" => SIsMember::extract_keys(&mut parse)?,
+            "smembers" => SMembers::extract_keys(&mut parse)?,
+            "srem" => SRem::extract_keys(&mut parse)?,
+            "strlen" => Strlen::extract_keys(&mut parse)?,
+            "subscribe" => Subscribe::extract_keys(&mut parse)?,
+            "ttl" => Ttl::extract_keys(&mut parse)?,
+            "type" => Type::extract_keys(&mut parse)?,
+            _ => return Err("Invalid command specified".into()),
+        };
+        Ok(keys)
+    }
+
     pub(crate) async fn apply(
         self,
         db: &Db,
@@ -49,24 +360,211 @@ impl Command {
         shutdown: &mut Shutdown,
     ) -> crate::Result<()> {
         use Command::*;
+        // 命令作用在当前连接通过 `SELECT` 选中的数据库上，编号在选中时已校验
+        let selected = db.select(dst.state.db_index).unwrap_or_else(|| db.clone());
+        let db = &selected;
+        // 统计每个命令的调用次数，未知命令不计入
+        if !matches!(self, Unknown(_)) {
+            db.record_command(self.get_name());
+        }
         match self {
+            Acl(cmd) => cmd.apply(db, dst).await,
+            Append(cmd) => cmd.apply(db, dst).await,
+            Auth(cmd) => cmd.apply(db, dst).await,
+            BitCount(cmd) => cmd.apply(db, dst).await,
+            ClientNoEvict(cmd) => cmd.apply(dst).await,
+            ClientNoTouch(cmd) => cmd.apply(dst).await,
+            Cluster(cmd) => cmd.apply(dst).await,
+            CommandCmd(cmd) => cmd.apply(dst).await,
+            DbSize(cmd) => cmd.apply(db, dst).await,
+            Debug(cmd) => cmd.apply(db, dst).await,
+            Decr(cmd) => cmd.apply(db, dst).await,
+            Del(cmd) => cmd.apply(db, dst).await,
+            Echo(cmd) => cmd.apply(dst).await,
+            Exists(cmd) => cmd.apply(db, dst).await,
+            Expire(cmd) => cmd.apply(db, dst).await,
+            FlushDb(cmd) => cmd.apply(db, dst).await,
             Get(cmd) => cmd.apply(db, dst).await,
+            GetDel(cmd) => cmd.apply(db, dst).await,
+            GetRange(cmd) => cmd.apply(db, dst).await,
+            GetSet(cmd) => cmd.apply(db, dst).await,
+            HDel(cmd) => cmd.apply(db, dst).await,
+            HGet(cmd) => cmd.apply(db, dst).await,
+            HGetAll(cmd) => cmd.apply(db, dst).await,
+            HSet(cmd) => cmd.apply(db, dst).await,
+            Hello(cmd) => cmd.apply(dst).await,
+            Incr(cmd) => cmd.apply(db, dst).await,
+            IncrByFloat(cmd) => cmd.apply(db, dst).await,
+            Info(cmd) => cmd.apply(db, dst).await,
+            Keys(cmd) => cmd.apply(db, dst).await,
+            LIndex(cmd) => cmd.apply(db, dst).await,
+            LLen(cmd) => cmd.apply(db, dst).await,
+            LPop(cmd) => cmd.apply(db, dst).await,
+            LPush(cmd) => cmd.apply(db, dst).await,
+            LRange(cmd) => cmd.apply(db, dst).await,
+            MGet(cmd) => cmd.apply(db, dst).await,
+            MSet(cmd) => cmd.apply(db, dst).await,
+            MSetNx(cmd) => cmd.apply(db, dst).await,
+            Move(cmd) => cmd.apply(db, dst).await,
+            Object(cmd) => cmd.apply(db, dst).await,
+            PSetEx(cmd) => cmd.apply(db, dst).await,
+            PSubscribe(cmd) => cmd.apply(db, dst, shutdown).await,
+            PUnsubscribe(_) => Err("punsubscribe is unsupported in this context".into()),
+            Persist(cmd) => cmd.apply(db, dst).await,
+            Ping(cmd) => cmd.apply(dst).await,
+            PubSub(cmd) => cmd.apply(db, dst).await,
             Publish(cmd) => cmd.apply(db, dst).await,
+            Quit(cmd) => cmd.apply(dst).await,
+            RPop(cmd) => cmd.apply(db, dst).await,
+            RPush(cmd) => cmd.apply(db, dst).await,
+            Rename(cmd) => cmd.apply(db, dst).await,
+            RenameNx(cmd) => cmd.apply(db, dst).await,
+            Reset(cmd) => cmd.apply(dst).await,
+            SAdd(cmd) => cmd.apply(db, dst).await,
+            SIsMember(cmd) => cmd.apply(db, dst).await,
+            SMembers(cmd) => cmd.apply(db, dst).await,
+            SRem(cmd) => cmd.apply(db, dst).await,
+            Scan(cmd) => cmd.apply(db, dst).await,
+            Select(cmd) => cmd.apply(db, dst).await,
             Set(cmd) => cmd.apply(db, dst).await,
+            SetEx(cmd) => cmd.apply(db, dst).await,
+            SetNx(cmd) => cmd.apply(db, dst).await,
+            Strlen(cmd) => cmd.apply(db, dst).await,
             Subscribe(cmd) => cmd.apply(db, dst, shutdown).await,
+            SwapDb(cmd) => cmd.apply(db, dst).await,
+            Ttl(cmd) => cmd.apply(db, dst).await,
+            Type(cmd) => cmd.apply(db, dst).await,
             Unsubscribe(_) => Err("unsubscribe si unsupproted in this context".into()),
             Unknown(cmd) => cmd.apply(dst).await,
         }
     }
 
+    // 服务器配置了密码时，未认证的连接也可以执行的命令
+    pub(crate) fn allowed_without_auth(&self) -> bool {
+        matches!(
+            self,
+            Command::Auth(_) | Command::Hello(_) | Command::Quit(_)
+        )
+    }
+
     pub(crate) fn get_name(&self) -> &str {
         match self {
+            Command::Acl(_) => "acl",
+            Command::Append(_) => "append",
+            Command::Auth(_) => "auth",
+            Command::BitCount(_) => "bitcount",
+            Command::ClientNoEvict(_) => "client|no-evict",
+            Command::ClientNoTouch(_) => "client|no-touch",
+            Command::Cluster(_) => "cluster",
+            Command::CommandCmd(_) => "command",
+            Command::DbSize(_) => "dbsize",
+            Command::Debug(_) => "debug",
+            Command::Decr(_) => "decr",
+            Command::Del(_) => "del",
+            Command::Echo(_) => "echo",
+            Command::Exists(_) => "exists",
+            Command::Expire(_) => "expire",
+            Command::FlushDb(_) => "flushdb",
             Command::Get(_) => "get",
-            Command::Publish(_) => "pub",
+            Command::GetDel(_) => "getdel",
+            Command::GetRange(_) => "getrange",
+            Command::GetSet(_) => "getset",
+            Command::HDel(_) => "hdel",
+            Command::HGet(_) => "hget",
+            Command::HGetAll(_) => "hgetall",
+            Command::HSet(_) => "hset",
+            Command::Hello(_) => "hello",
+            Command::Incr(_) => "incr",
+            Command::IncrByFloat(_) => "incrbyfloat",
+            Command::Info(_) => "info",
+            Command::Keys(_) => "keys",
+            Command::LIndex(_) => "lindex",
+            Command::LLen(_) => "llen",
+            Command::LPop(_) => "lpop",
+            Command::LPush(_) => "lpush",
+            Command::LRange(_) => "lrange",
+            Command::MGet(_) => "mget",
+            Command::MSet(_) => "mset",
+            Command::MSetNx(_) => "msetnx",
+            Command::Move(_) => "move",
+            Command::Object(_) => "object",
+            Command::PSetEx(_) => "psetex",
+            Command::PSubscribe(_) => "psubscribe",
+            Command::PUnsubscribe(_) => "punsubscribe",
+            Command::Persist(_) => "persist",
+            Command::Ping(_) => "ping",
+            Command::PubSub(_) => "pubsub",
+            Command::Publish(_) => "publish",
+            Command::Quit(_) => "quit",
+            Command::RPop(_) => "rpop",
+            Command::RPush(_) => "rpush",
+            Command::Rename(_) => "rename",
+            Command::RenameNx(_) => "renamenx",
+            Command::Reset(_) => "reset",
+            Command::SAdd(_) => "sadd",
+            Command::SIsMember(_) => "sismember",
+            Command::SMembers(_) => "smembers",
+            Command::SRem(_) => "srem",
+            Command::Scan(_) => "scan",
+            Command::Select(_) => "select",
             Command::Set(_) => "set",
+            Command::SetEx(_) => "setex",
+            Command::SetNx(_) => "setnx",
+            Command::Strlen(_) => "strlen",
             Command::Subscribe(_) => "subscribe",
+            Command::SwapDb(_) => "swapdb",
+            Command::Ttl(_) => "ttl",
+            Command::Type(_) => "type",
             Command::Unsubscribe(_) => "unsubscribe",
             Command::Unknown(cmd) => cmd.get_name(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{DEFAULT_SET_MAX_LISTPACK_ENTRIES, DEFAULT_SET_MAX_LISTPACK_VALUE};
+    use bytes::Bytes;
+    use tokio::sync::broadcast;
+
+    // 在内存管道上执行一条命令，返回服务器的回复
+    async fn run(db: &Db, args: &[&str]) -> Frame {
+        let (client, server) = tokio::io::duplex(4096);
+        let mut client: Connection = Connection::new(Box::new(client));
+        let mut server: Connection = Connection::new(Box::new(server));
+        let (_notify_shutdown, notify) = broadcast::channel(1);
+        let mut shutdown = Shutdown::new(notify);
+
+        let mut frame = Frame::array();
+        for arg in args {
+            frame.push_bulk(Bytes::from(arg.to_string()));
+        }
+        let command = Command::from_frame(frame).unwrap();
+        command.apply(db, &mut server, &mut shutdown).await.unwrap();
+        client.read_frame().await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn substr_behaves_like_getrange() {
+        let db = Db::new(
+            1,
+            16,
+            None,
+            DEFAULT_SET_MAX_LISTPACK_ENTRIES,
+            DEFAULT_SET_MAX_LISTPACK_VALUE,
+        );
+        db.set(
+            "mykey".to_string(),
+            Bytes::from("hello world"),
+            None,
+            None,
+            false,
+        );
+
+        let substr = run(&db, &["SUBSTR", "mykey", "0", "3"]).await;
+        let getrange = run(&db, &["GETRANGE", "mykey", "0", "3"]).await;
+        assert!(matches!(&substr, Frame::Bulk(data) if data == "hell"));
+        assert_eq!(format!("{:?}", substr), format!("{:?}", getrange));
+    }
+}
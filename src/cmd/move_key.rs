@@ -0,0 +1,58 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Move {
+    key: String,
+    db: i64,
+}
+
+impl Move {
+    pub fn new(key: impl ToString, db: i64) -> Move {
+        Move {
+            key: key.to_string(),
+            db,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Move> {
+        let key = parse.next_string()?;
+        let db = parse.next_signed_int()?;
+        Ok(Move { key, db })
+    }
+
+    /// 将键连同剩余的过期时间移动到另一个数据库，成功时回复 1
+    /// 键不存在或目标数据库中已存在同名的键时回复 0
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let moved = usize::try_from(self.db)
+            .map_err(|_| "ERR DB index is out of range".into())
+            .and_then(|index| db.move_key(&self.key, index));
+        let response = match moved {
+            Ok(moved) => Frame::Integer(moved as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("move".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.db.to_string()));
+        frame
+    }
+}
+
+impl KeyExtractor for Move {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
@@ -0,0 +1,70 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct MSet {
+    pairs: Vec<(String, Bytes)>,
+}
+
+impl MSet {
+    pub fn new(pairs: Vec<(String, Bytes)>) -> MSet {
+        MSet { pairs }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<MSet> {
+        use ParseError::EndOfStream;
+
+        let mut pairs = vec![(parse.next_string()?, parse.next_bytes()?)];
+
+        loop {
+            let key = match parse.next_string() {
+                Ok(key) => key,
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            };
+            let value = match parse.next_bytes() {
+                Ok(value) => value,
+                Err(EndOfStream) => {
+                    return Err("ERR wrong number of arguments for 'mset' command".into());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            pairs.push((key, value));
+        }
+        Ok(MSet { pairs })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        db.mset(self.pairs);
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("mset".as_bytes()));
+        for (key, value) in self.pairs {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+            frame.push_bulk(value);
+        }
+        frame
+    }
+}
+
+impl KeyExtractor for MSet {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(MSet::parse_frames(parse)?
+            .pairs
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect())
+    }
+}
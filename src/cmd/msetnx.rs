@@ -0,0 +1,69 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct MSetNx {
+    pairs: Vec<(String, Bytes)>,
+}
+
+impl MSetNx {
+    pub fn new(pairs: Vec<(String, Bytes)>) -> MSetNx {
+        MSetNx { pairs }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<MSetNx> {
+        use ParseError::EndOfStream;
+
+        let mut pairs = vec![(parse.next_string()?, parse.next_bytes()?)];
+
+        loop {
+            let key = match parse.next_string() {
+                Ok(key) => key,
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            };
+            let value = match parse.next_bytes() {
+                Ok(value) => value,
+                Err(EndOfStream) => {
+                    return Err("ERR wrong number of arguments for 'msetnx' command".into());
+                }
+                Err(e) => return Err(e.into()),
+            };
+            pairs.push((key, value));
+        }
+        Ok(MSetNx { pairs })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = Frame::Integer(db.msetnx(self.pairs) as i64);
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("msetnx".as_bytes()));
+        for (key, value) in self.pairs {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+            frame.push_bulk(value);
+        }
+        frame
+    }
+}
+
+impl KeyExtractor for MSetNx {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(MSetNx::parse_frames(parse)?
+            .pairs
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect())
+    }
+}
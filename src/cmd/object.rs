@@ -0,0 +1,50 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Object {
+    subcommand: ObjectSubcommand,
+}
+
+#[derive(Debug)]
+pub enum ObjectSubcommand {
+    Encoding { key: String },
+    Freq { key: String },
+}
+
+impl Object {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Object> {
+        let subcommand = parse.next_string()?.to_lowercase();
+        let subcommand = match &subcommand[..] {
+            "encoding" => ObjectSubcommand::Encoding {
+                key: parse.next_string()?,
+            },
+            "freq" => ObjectSubcommand::Freq {
+                key: parse.next_string()?,
+            },
+            _ => return Err(format!("unknown `object` subcommand '{}'", subcommand).into()),
+        };
+        Ok(Object { subcommand })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            ObjectSubcommand::Encoding { key } => match db.object_encoding(&key) {
+                Some(encoding) => Frame::Bulk(Bytes::from_static(encoding.as_bytes())),
+                None => Frame::Null,
+            },
+            ObjectSubcommand::Freq { key } => match db.object_freq(&key) {
+                Some(freq) => Frame::Integer(freq as i64),
+                None => Frame::Null,
+            },
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
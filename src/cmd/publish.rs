@@ -1,3 +1,4 @@
+use crate::cmd::KeyExtractor;
 use crate::connection::Connection;
 use crate::db::Db;
 use crate::frame::Frame;
@@ -26,7 +27,7 @@ impl Publish {
 
     pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
         let num_subscribers = db.publish(&self.channel, self.message);
-        let response = Frame::Integer(num_subscribers as u64);
+        let response = Frame::Integer(num_subscribers as i64);
         let _ = dst.write_frame(&response).await;
         Ok(())
     }
@@ -39,3 +40,9 @@ impl Publish {
         frame
     }
 }
+
+impl KeyExtractor for Publish {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
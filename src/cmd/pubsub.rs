@@ -0,0 +1,105 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct PubSub {
+    subcommand: PubSubSubcommand,
+}
+
+#[derive(Debug)]
+pub enum PubSubSubcommand {
+    Channels { pattern: Option<String> },
+    NumSub { channels: Vec<String> },
+}
+
+impl PubSub {
+    pub(crate) fn channels(pattern: Option<String>) -> PubSub {
+        PubSub {
+            subcommand: PubSubSubcommand::Channels { pattern },
+        }
+    }
+
+    pub(crate) fn numsub(channels: Vec<String>) -> PubSub {
+        PubSub {
+            subcommand: PubSubSubcommand::NumSub { channels },
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<PubSub> {
+        use ParseError::EndOfStream;
+
+        let subcommand = parse.next_string()?.to_lowercase();
+        let subcommand = match &subcommand[..] {
+            "channels" => {
+                let pattern = match parse.next_string() {
+                    Ok(pattern) => Some(pattern),
+                    Err(EndOfStream) => None,
+                    Err(e) => return Err(e.into()),
+                };
+                parse.finish()?;
+                PubSubSubcommand::Channels { pattern }
+            }
+            "numsub" => {
+                let mut channels = vec![];
+                loop {
+                    match parse.next_string() {
+                        Ok(channel) => channels.push(channel),
+                        Err(EndOfStream) => break,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                PubSubSubcommand::NumSub { channels }
+            }
+            _ => return Err(format!("unknown `pubsub` subcommand '{}'", subcommand).into()),
+        };
+        Ok(PubSub { subcommand })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.subcommand {
+            PubSubSubcommand::Channels { pattern } => Frame::Array(
+                db.pubsub_channels(pattern.as_deref())
+                    .into_iter()
+                    .map(|channel| Frame::Bulk(Bytes::from(channel)))
+                    .collect(),
+            ),
+            // 回复为频道名与订阅者数量交替排列的扁平数组
+            PubSubSubcommand::NumSub { channels } => {
+                let mut frame = Frame::array();
+                for (channel, num) in db.pubsub_numsub(&channels) {
+                    frame.push_bulk(Bytes::from(channel));
+                    frame.push_int(num as i64);
+                }
+                frame
+            }
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("pubsub".as_bytes()));
+        match self.subcommand {
+            PubSubSubcommand::Channels { pattern } => {
+                frame.push_bulk(Bytes::from("channels".as_bytes()));
+                if let Some(pattern) = pattern {
+                    frame.push_bulk(Bytes::from(pattern.into_bytes()));
+                }
+            }
+            PubSubSubcommand::NumSub { channels } => {
+                frame.push_bulk(Bytes::from("numsub".as_bytes()));
+                for channel in channels {
+                    frame.push_bulk(Bytes::from(channel.into_bytes()));
+                }
+            }
+        }
+        frame
+    }
+}
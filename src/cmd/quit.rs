@@ -0,0 +1,33 @@
+use crate::connection::Connection;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug, Default)]
+pub struct Quit;
+
+impl Quit {
+    pub fn new() -> Quit {
+        Quit
+    }
+
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> crate::Result<Quit> {
+        Ok(Quit)
+    }
+
+    /// 回复 OK 后由连接的处理循环关闭连接，不再读取之后的命令
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        dst.state.closing = true;
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("quit".as_bytes()));
+        frame
+    }
+}
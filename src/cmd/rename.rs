@@ -0,0 +1,101 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Rename {
+    key: String,
+    new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RenameNx {
+    key: String,
+    new_key: String,
+}
+
+impl Rename {
+    pub fn new(key: impl ToString, new_key: impl ToString) -> Rename {
+        Rename {
+            key: key.to_string(),
+            new_key: new_key.to_string(),
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Rename> {
+        let key = parse.next_string()?;
+        let new_key = parse.next_string()?;
+        Ok(Rename { key, new_key })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.rename(&self.key, &self.new_key) {
+            Ok(()) => Frame::Simple("OK".to_string()),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("rename".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.new_key.into_bytes()));
+        frame
+    }
+}
+
+impl RenameNx {
+    pub fn new(key: impl ToString, new_key: impl ToString) -> RenameNx {
+        RenameNx {
+            key: key.to_string(),
+            new_key: new_key.to_string(),
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<RenameNx> {
+        let key = parse.next_string()?;
+        let new_key = parse.next_string()?;
+        Ok(RenameNx { key, new_key })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match db.rename_nx(&self.key, &self.new_key) {
+            Ok(renamed) => Frame::Integer(renamed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("renamenx".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.new_key.into_bytes()));
+        frame
+    }
+}
+
+impl KeyExtractor for Rename {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        let rename = Rename::parse_frames(parse)?;
+        Ok(vec![rename.key, rename.new_key])
+    }
+}
+
+impl KeyExtractor for RenameNx {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        let rename = RenameNx::parse_frames(parse)?;
+        Ok(vec![rename.key, rename.new_key])
+    }
+}
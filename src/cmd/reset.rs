@@ -0,0 +1,34 @@
+use crate::connection::{Connection, ConnectionState};
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug, Default)]
+pub struct Reset;
+
+impl Reset {
+    pub fn new() -> Reset {
+        Reset
+    }
+
+    pub(crate) fn parse_frames(_parse: &mut Parse) -> crate::Result<Reset> {
+        Ok(Reset)
+    }
+
+    /// 将连接恢复到刚建立时的状态，包括 `CLIENT NO-TOUCH` 等所有连接级别的设置。
+    /// 订阅状态由订阅循环在收到 `RESET` 时自行清理
+    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+        dst.state = ConnectionState::default();
+        let response = Frame::Simple("RESET".to_string());
+        debug!(?response);
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("reset".as_bytes()));
+        frame
+    }
+}
@@ -0,0 +1,88 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::{Parse, ParseError};
+use bytes::Bytes;
+use tracing::debug;
+
+/// 未指定 `COUNT` 时每次遍历的键数量
+const DEFAULT_COUNT: u64 = 10;
+
+#[derive(Debug)]
+pub struct Scan {
+    cursor: u64,
+    pattern: Option<String>,
+    count: Option<u64>,
+}
+
+impl Scan {
+    pub fn new(cursor: u64, pattern: Option<String>, count: Option<u64>) -> Scan {
+        Scan {
+            cursor,
+            pattern,
+            count,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Scan> {
+        use ParseError::EndOfStream;
+
+        let cursor = parse.next_int()?;
+        let mut pattern = None;
+        let mut count = None;
+
+        loop {
+            let option = match parse.next_string() {
+                Ok(s) => s.to_uppercase(),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            };
+            match &option[..] {
+                "MATCH" => pattern = Some(parse.next_string()?),
+                "COUNT" => match parse.next_int()? {
+                    0 => return Err("ERR syntax error".into()),
+                    n => count = Some(n),
+                },
+                _ => return Err("ERR syntax error".into()),
+            }
+        }
+        Ok(Scan {
+            cursor,
+            pattern,
+            count,
+        })
+    }
+
+    /// 返回 `[下一个游标, [键...]]`，`MATCH` 在取出键之后过滤，因此一次遍历可能返回空列表但游标不为 0
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let count = self.count.unwrap_or(DEFAULT_COUNT) as usize;
+        let (next, keys) = db.scan(self.cursor, self.pattern.as_deref(), count);
+        let response = Frame::Array(vec![
+            Frame::Bulk(Bytes::from(next.to_string())),
+            Frame::Array(
+                keys.into_iter()
+                    .map(|key| Frame::Bulk(Bytes::from(key)))
+                    .collect(),
+            ),
+        ]);
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("scan".as_bytes()));
+        frame.push_bulk(Bytes::from(self.cursor.to_string()));
+        if let Some(pattern) = self.pattern {
+            frame.push_bulk(Bytes::from("MATCH".as_bytes()));
+            frame.push_bulk(Bytes::from(pattern.into_bytes()));
+        }
+        if let Some(count) = self.count {
+            frame.push_bulk(Bytes::from("COUNT".as_bytes()));
+            frame.push_bulk(Bytes::from(count.to_string()));
+        }
+        frame
+    }
+}
@@ -0,0 +1,45 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct Select {
+    index: i64,
+}
+
+impl Select {
+    pub fn new(index: i64) -> Select {
+        Select { index }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<Select> {
+        // 与 Redis 一致，负数编号回复超出范围，而不是解析错误
+        let index = parse.next_signed_int()?;
+        Ok(Select { index })
+    }
+
+    /// 切换当前连接使用的数据库，之后的命令都作用在选中的数据库上
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let response = match usize::try_from(self.index) {
+            Ok(index) if index < db.databases() => {
+                dst.state.db_index = index;
+                Frame::Simple("OK".to_string())
+            }
+            _ => Frame::Error("ERR DB index is out of range".to_string()),
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("select".as_bytes()));
+        frame.push_bulk(Bytes::from(self.index.to_string()));
+        frame
+    }
+}
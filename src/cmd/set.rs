@@ -1,3 +1,4 @@
+use crate::cmd::KeyExtractor;
 use crate::connection::Connection;
 use crate::db::Db;
 use crate::frame::Frame;
@@ -11,6 +12,17 @@ pub struct Set {
     key: String,
     value: Bytes,
     expire: Option<Duration>,
+    condition: Option<SetCondition>,
+    keep_ttl: bool,
+}
+
+/// `SET` 的写入条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    /// `NX`：仅当键不存在时写入
+    NotExists,
+    /// `XX`：仅当键已存在时写入
+    Exists,
 }
 
 impl Set {
@@ -19,6 +31,8 @@ impl Set {
             key: key.to_string(),
             value,
             expire,
+            condition: None,
+            keep_ttl: false,
         }
     }
 
@@ -41,26 +55,54 @@ impl Set {
         let key = parse.next_string()?;
         let value = parse.next_bytes()?;
         let mut expire = None;
+        let mut condition = None;
+        let mut keep_ttl = false;
 
-        match parse.next_string() {
-            Ok(s) if s == "EX" => {
-                let secs = parse.next_int()?;
-                expire = Some(Duration::from_secs(secs));
-            }
-            Ok(s) if s == "PX" => {
-                let ms = parse.next_int()?;
-                expire = Some(Duration::from_millis(ms));
+        // 选项可以按任意顺序出现，但互相冲突的选项不能同时使用
+        loop {
+            let option = match parse.next_string() {
+                Ok(s) => s.to_uppercase(),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            };
+            match &option[..] {
+                "EX" if expire.is_none() && !keep_ttl => {
+                    let secs = parse.next_int()?;
+                    expire = Some(Duration::from_secs(secs));
+                }
+                "PX" if expire.is_none() && !keep_ttl => {
+                    let ms = parse.next_int()?;
+                    expire = Some(Duration::from_millis(ms));
+                }
+                "NX" if condition.is_none() => condition = Some(SetCondition::NotExists),
+                "XX" if condition.is_none() => condition = Some(SetCondition::Exists),
+                "KEEPTTL" if expire.is_none() => keep_ttl = true,
+                _ => return Err("ERR syntax error".into()),
             }
-            Ok(_) => return Err("currently `set` only supports the expiration option".into()),
-            Err(EndOfStream) => {}
-            Err(e) => return Err(e.into()),
         }
-        Ok(Set { key, value, expire })
+        Ok(Set {
+            key,
+            value,
+            expire,
+            condition,
+            keep_ttl,
+        })
     }
 
     pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
-        db.set(self.key, self.value, self.expire);
-        let response = Frame::Simple("OK".to_string());
+        let written = db.set(
+            self.key,
+            self.value,
+            self.expire,
+            self.condition,
+            self.keep_ttl,
+        );
+        // `NX`/`XX` 的条件不满足时返回空值
+        let response = if written {
+            Frame::Simple("OK".to_string())
+        } else {
+            Frame::Null
+        };
         debug!(?response);
         dst.write_frame(&response).await?;
         Ok(())
@@ -71,6 +113,52 @@ impl Set {
         frame.push_bulk(Bytes::from("set".as_bytes()));
         frame.push_bulk(Bytes::from(self.key.into_bytes()));
         frame.push_bulk(self.value);
+        if let Some(expire) = self.expire {
+            frame.push_bulk(Bytes::from("PX".as_bytes()));
+            frame.push_bulk(Bytes::from(expire.as_millis().to_string()));
+        }
+        match self.condition {
+            Some(SetCondition::NotExists) => frame.push_bulk(Bytes::from("NX".as_bytes())),
+            Some(SetCondition::Exists) => frame.push_bulk(Bytes::from("XX".as_bytes())),
+            None => {}
+        }
+        if self.keep_ttl {
+            frame.push_bulk(Bytes::from("KEEPTTL".as_bytes()));
+        }
         frame
     }
 }
+
+impl KeyExtractor for Set {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> crate::Result<Set> {
+        let mut frame = Frame::array();
+        for arg in args {
+            frame.push_bulk(Bytes::from(arg.to_string()));
+        }
+        let mut parse = Parse::new(frame)?;
+        parse.next_string()?;
+        Set::parse_frames(&mut parse)
+    }
+
+    #[test]
+    fn conflicting_options_are_syntax_errors() {
+        let cases: [&[&str]; 3] = [
+            &["SET", "k", "v", "NX", "XX"],
+            &["SET", "k", "v", "EX", "10", "PX", "100"],
+            &["SET", "k", "v", "KEEPTTL", "EX", "10"],
+        ];
+        for args in cases {
+            let err = parse(args).unwrap_err();
+            assert_eq!(err.to_string(), "ERR syntax error", "{:?}", args);
+        }
+    }
+}
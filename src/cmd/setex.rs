@@ -0,0 +1,121 @@
+use crate::cmd::KeyExtractor;
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use std::time::Duration;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct SetEx {
+    key: String,
+    seconds: i64,
+    value: Bytes,
+}
+
+#[derive(Debug)]
+pub struct PSetEx {
+    key: String,
+    millis: i64,
+    value: Bytes,
+}
+
+impl SetEx {
+    pub fn new(key: impl ToString, seconds: i64, value: Bytes) -> SetEx {
+        SetEx {
+            key: key.to_string(),
+            seconds,
+            value,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<SetEx> {
+        let key = parse.next_string()?;
+        let seconds = parse.next_signed_int()?;
+        let value = parse.next_bytes()?;
+        Ok(SetEx {
+            key,
+            seconds,
+            value,
+        })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let expire = u64::try_from(self.seconds).ok().map(Duration::from_secs);
+        apply_with_expire(db, dst, self.key, self.value, expire).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("setex".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.seconds.to_string()));
+        frame.push_bulk(self.value);
+        frame
+    }
+}
+
+impl PSetEx {
+    pub fn new(key: impl ToString, millis: i64, value: Bytes) -> PSetEx {
+        PSetEx {
+            key: key.to_string(),
+            millis,
+            value,
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<PSetEx> {
+        let key = parse.next_string()?;
+        let millis = parse.next_signed_int()?;
+        let value = parse.next_bytes()?;
+        Ok(PSetEx { key, millis, value })
+    }
+
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let expire = u64::try_from(self.millis).ok().map(Duration::from_millis);
+        apply_with_expire(db, dst, self.key, self.value, expire).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("psetex".as_bytes()));
+        frame.push_bulk(Bytes::from(self.key.into_bytes()));
+        frame.push_bulk(Bytes::from(self.millis.to_string()));
+        frame.push_bulk(self.value);
+        frame
+    }
+}
+
+/// 写入带过期时间的值，过期时间必须为正数，否则返回错误帧
+async fn apply_with_expire(
+    db: &Db,
+    dst: &mut Connection,
+    key: String,
+    value: Bytes,
+    expire: Option<Duration>,
+) -> crate::Result<()> {
+    let response = match expire.filter(|expire| !expire.is_zero()) {
+        Some(expire) => {
+            db.set(key, value, Some(expire), None, false);
+            Frame::Simple("OK".to_string())
+        }
+        None => Frame::Error("ERR invalid expire time".to_string()),
+    };
+    debug!(?response);
+
+    dst.write_frame(&response).await?;
+    Ok(())
+}
+
+impl KeyExtractor for SetEx {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
+
+impl KeyExtractor for PSetEx {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(vec![parse.next_string()?])
+    }
+}
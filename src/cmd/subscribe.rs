@@ -1,4 +1,4 @@
-use crate::cmd::{Command, Unknown};
+use crate::cmd::{Command, KeyExtractor, Unknown};
 use crate::connection::Connection;
 use crate::db::Db;
 use crate::frame::Frame;
@@ -7,7 +7,13 @@ use crate::shutdown::Shutdown;
 use bytes::Bytes;
 use tokio::select;
 use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
 use tokio_stream::{StreamExt, StreamMap};
+use tracing::warn;
+
+/// 订阅者落后时服务器发送的错误通知的前缀，客户端据此区分通知和真正的错误
+pub(crate) const LAGGED_PREFIX: &str = "ERR subscriber lagged behind";
+
 #[derive(Debug)]
 pub struct Subscribe {
     channels: Vec<String>,
@@ -18,6 +24,36 @@ pub struct Unsubscribe {
     channels: Vec<String>,
 }
 
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<String>,
+}
+
+/// 订阅模式下连接持有的所有订阅，频道和模式各自对应一个流映射
+struct Subscriptions {
+    channels: StreamMap<String, BroadcastStream<Bytes>>,
+    patterns: StreamMap<String, BroadcastStream<(String, Bytes)>>,
+}
+
+impl Subscriptions {
+    fn new() -> Subscriptions {
+        Subscriptions {
+            channels: StreamMap::new(),
+            patterns: StreamMap::new(),
+        }
+    }
+
+    /// 订阅的频道和模式的总数，即订阅相关回复中携带的数量
+    fn len(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+}
+
 impl Subscribe {
     pub(crate) fn new(channels: &[String]) -> Subscribe {
         Subscribe {
@@ -56,44 +92,12 @@ impl Subscribe {
     ///
     /// 返回一个 `crate::Result<()>`，表示操作的成功或失败。
     pub(crate) async fn apply(
-        mut self,
+        self,
         db: &Db,
         dst: &mut Connection,
         shutdow: &mut Shutdown,
     ) -> crate::Result<()> {
-        // 创建一个新的流映射来存储订阅的频道和它们的接收器
-        let mut subs = StreamMap::new();
-        loop {
-            // 遍历所有要订阅的频道
-            for channel_name in self.channels.drain(..) {
-                // 为每个频道订阅并将其添加到流映射中
-                subscribe_to_channel(channel_name, &mut subs, db, dst).await?;
-            }
-            // 使用 `select!` 宏来同时等待多个异步操作
-            select! {
-                // 当从订阅的频道接收到消息时
-                Some((channel_name,msg))=subs.next()=>{
-                    // 处理接收到的消息
-                    let msg = match msg{
-                        Ok(msg) => msg,
-                        Err(_) => unreachable!(),
-                    };
-                    // 将消息发送回客户端
-                    dst.write_frame(&make_message_frame(channel_name,msg)).await?;
-                }
-                // 当从客户端接收到命令时
-                res = dst.read_frame()=>{
-                    // 处理接收到的命令
-                    let frame = match res?{
-                        Some(frame)=>frame,
-                        None=>return Ok(()),
-                    };
-                    handle_command(frame,&mut self.channels,&mut subs,dst).await?;
-                }
-                // 当接收到关闭信号时
-                _=shutdow.recv()=>return Ok(()),
-            }
-        }
+        subscribe_loop(self.channels, vec![], db, dst, shutdow).await
     }
 
     pub(crate) fn into_frame(self) -> Frame {
@@ -106,74 +110,203 @@ impl Subscribe {
     }
 }
 
+/// 订阅模式的主循环，由 `SUBSCRIBE` 和 `PSUBSCRIBE` 共用
+///
+/// 先订阅待订阅的频道和模式，然后同时等待订阅的消息、客户端发来的命令和关闭信号。
+/// 订阅模式下只接受订阅相关的命令、`RESET` 和 `QUIT`
+async fn subscribe_loop(
+    mut channels: Vec<String>,
+    mut patterns: Vec<String>,
+    db: &Db,
+    dst: &mut Connection,
+    shutdow: &mut Shutdown,
+) -> crate::Result<()> {
+    let mut subs = Subscriptions::new();
+    loop {
+        // 遍历所有要订阅的频道和模式，订阅并将其添加到流映射中
+        for channel_name in channels.drain(..) {
+            subscribe_to_channel(channel_name, &mut subs, db, dst).await?;
+        }
+        for pattern in patterns.drain(..) {
+            subscribe_to_pattern(pattern, &mut subs, db, dst).await?;
+        }
+        // 使用 `select!` 宏来同时等待多个异步操作
+        select! {
+            // 当从订阅的频道接收到消息时
+            Some((channel_name,msg))=subs.channels.next()=>{
+                let frame = match msg{
+                    Ok(msg) => make_message_frame(channel_name,msg),
+                    Err(BroadcastStreamRecvError::Lagged(n)) => {
+                        warn!(channel = %channel_name, skipped = n, "subscriber lagged");
+                        make_lagged_frame(&channel_name,n)
+                    }
+                };
+                dst.write_frame(&frame).await?;
+            }
+            // 当从订阅的模式接收到消息时，消息中携带实际发布的频道名
+            Some((pattern,msg))=subs.patterns.next()=>{
+                let frame = match msg{
+                    Ok((channel_name, msg)) => make_pmessage_frame(pattern,channel_name,msg),
+                    Err(BroadcastStreamRecvError::Lagged(n)) => {
+                        warn!(%pattern, skipped = n, "pattern subscriber lagged");
+                        make_lagged_frame(&pattern,n)
+                    }
+                };
+                dst.write_frame(&frame).await?;
+            }
+            // 当从客户端接收到命令时
+            res = dst.read_frame()=>{
+                let frame = match res?{
+                    Some(frame)=>frame,
+                    None=>return Ok(()),
+                };
+                // 收到 `RESET` 或 `QUIT` 后退订所有频道和模式并退出订阅模式
+                if handle_command(frame,&mut channels,&mut patterns,&mut subs,dst).await? {
+                    return Ok(());
+                }
+            }
+            // 当接收到关闭信号时
+            _=shutdow.recv()=>return Ok(()),
+        }
+    }
+}
+
 async fn subscribe_to_channel(
     channel_name: String,
-    subscriptions: &mut StreamMap<String, BroadcastStream<Bytes>>,
+    subscriptions: &mut Subscriptions,
     db: &Db,
     dst: &mut Connection,
 ) -> crate::Result<()> {
     let rx = db.subscribe(channel_name.clone());
-    subscriptions.insert(channel_name.clone(), BroadcastStream::new(rx));
-    let response = make_subscribe_frame(channel_name, subscriptions.len());
+    subscriptions
+        .channels
+        .insert(channel_name.clone(), BroadcastStream::new(rx));
+    let response = make_subscription_frame("subscribe", channel_name, subscriptions.len());
     dst.write_frame(&response).await?;
     Ok(())
 }
 
+async fn subscribe_to_pattern(
+    pattern: String,
+    subscriptions: &mut Subscriptions,
+    db: &Db,
+    dst: &mut Connection,
+) -> crate::Result<()> {
+    let rx = db.psubscribe(pattern.clone());
+    subscriptions
+        .patterns
+        .insert(pattern.clone(), BroadcastStream::new(rx));
+    let response = make_subscription_frame("psubscribe", pattern, subscriptions.len());
+    dst.write_frame(&response).await?;
+    Ok(())
+}
+
+/// 处理订阅模式下收到的命令，返回 `true` 表示连接应当退出订阅模式
 async fn handle_command(
     frame: Frame,
     subscribe_to: &mut Vec<String>,
-    subscriptions: &mut StreamMap<String, BroadcastStream<Bytes>>,
+    psubscribe_to: &mut Vec<String>,
+    subscriptions: &mut Subscriptions,
     dst: &mut Connection,
-) -> crate::Result<()> {
+) -> crate::Result<bool> {
     match Command::from_frame(frame)? {
         Command::Subscribe(subscribe) => {
-            subscribe_to.extend(subscribe.channels.into_iter());
+            subscribe_to.extend(subscribe.channels);
+        }
+        Command::PSubscribe(psubscribe) => {
+            psubscribe_to.extend(psubscribe.patterns);
         }
         Command::Unsubscribe(mut unsubscribe) => {
             if unsubscribe.channels.is_empty() {
                 unsubscribe.channels = subscriptions
+                    .channels
                     .keys()
                     .map(|channel_name| channel_name.to_string())
                     .collect();
             }
             for channel_name in unsubscribe.channels {
-                subscriptions.remove(&channel_name);
-                let response = make_unsubscribe_frame(channel_name, subscriptions.len());
+                subscriptions.channels.remove(&channel_name);
+                let response =
+                    make_subscription_frame("unsubscribe", channel_name, subscriptions.len());
                 dst.write_frame(&response).await?;
             }
         }
+        Command::PUnsubscribe(mut punsubscribe) => {
+            if punsubscribe.patterns.is_empty() {
+                punsubscribe.patterns = subscriptions
+                    .patterns
+                    .keys()
+                    .map(|pattern| pattern.to_string())
+                    .collect();
+            }
+            for pattern in punsubscribe.patterns {
+                subscriptions.patterns.remove(&pattern);
+                let response =
+                    make_subscription_frame("punsubscribe", pattern, subscriptions.len());
+                dst.write_frame(&response).await?;
+            }
+        }
+        Command::Reset(reset) => {
+            subscribe_to.clear();
+            psubscribe_to.clear();
+            subscriptions.channels.clear();
+            subscriptions.patterns.clear();
+            reset.apply(dst).await?;
+            return Ok(true);
+        }
+        // 退出订阅模式后由连接的处理循环关闭连接
+        Command::Quit(quit) => {
+            quit.apply(dst).await?;
+            return Ok(true);
+        }
         command => {
             let cmd = Unknown::new(command.get_name());
             cmd.apply(dst).await?;
         }
     }
-    Ok(())
+    Ok(false)
 }
 
-fn make_subscribe_frame(channel_name: String, num_subs: usize) -> Frame {
+/// 订阅或退订的确认帧：`[kind, 频道或模式, 当前订阅总数]`
+fn make_subscription_frame(kind: &'static str, name: String, num_subs: usize) -> Frame {
     let mut response = Frame::array();
-    response.push_bulk(Bytes::from_static(b"subscribe"));
-    response.push_bulk(Bytes::from(channel_name));
-    response.push_int(num_subs as u64);
+    response.push_bulk(Bytes::from_static(kind.as_bytes()));
+    response.push_bulk(Bytes::from(name));
+    response.push_int(num_subs as i64);
     response
 }
 
-fn make_unsubscribe_frame(channel_name: String, num_subs: usize) -> Frame {
+fn make_message_frame(channel_name: String, msg: Bytes) -> Frame {
     let mut response = Frame::array();
-    response.push_bulk(Bytes::from_static(b"unsubscribe"));
+    response.push_bulk(Bytes::from_static(b"message"));
     response.push_bulk(Bytes::from(channel_name));
-    response.push_int(num_subs as u64);
+    response.push_bulk(msg);
     response
 }
 
-fn make_message_frame(channel_name: String, msg: Bytes) -> Frame {
+fn make_pmessage_frame(pattern: String, channel_name: String, msg: Bytes) -> Frame {
     let mut response = Frame::array();
-    response.push_bulk(Bytes::from_static(b"message"));
+    response.push_bulk(Bytes::from_static(b"pmessage"));
+    response.push_bulk(Bytes::from(pattern));
     response.push_bulk(Bytes::from(channel_name));
     response.push_bulk(msg);
     response
 }
 
+// 订阅者处理过慢、频道缓冲溢出时，通知客户端有消息被丢弃，订阅本身保持不变
+fn make_lagged_frame(name: &str, skipped: u64) -> Frame {
+    Frame::Error(format!(
+        "{} on '{}', {} messages dropped",
+        LAGGED_PREFIX, name, skipped
+    ))
+}
+
+impl KeyExtractor for Subscribe {
+    fn extract_keys(parse: &mut Parse) -> crate::Result<Vec<String>> {
+        Ok(Subscribe::parse_frames(parse)?.channels)
+    }
+}
+
 impl Unsubscribe {
     pub(crate) fn new(channels: &[String]) -> Unsubscribe {
         Unsubscribe {
@@ -205,3 +338,173 @@ impl Unsubscribe {
         frame
     }
 }
+
+impl PSubscribe {
+    pub(crate) fn new(patterns: &[String]) -> PSubscribe {
+        PSubscribe {
+            patterns: patterns.to_vec(),
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<PSubscribe> {
+        use ParseError::EndOfStream;
+
+        let mut patterns = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(s) => patterns.push(s),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(PSubscribe { patterns })
+    }
+
+    /// 按模式订阅并进入订阅模式，与频道名匹配的消息以 `pmessage` 帧发送给客户端
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        dst: &mut Connection,
+        shutdow: &mut Shutdown,
+    ) -> crate::Result<()> {
+        subscribe_loop(vec![], self.patterns, db, dst, shutdow).await
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("psubscribe".as_bytes()));
+        for pattern in self.patterns {
+            frame.push_bulk(Bytes::from(pattern.into_bytes()));
+        }
+        frame
+    }
+}
+
+impl PUnsubscribe {
+    pub(crate) fn new(patterns: &[String]) -> PUnsubscribe {
+        PUnsubscribe {
+            patterns: patterns.to_vec(),
+        }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<PUnsubscribe, ParseError> {
+        use ParseError::EndOfStream;
+
+        let mut patterns = vec![];
+
+        loop {
+            match parse.next_string() {
+                Ok(s) => patterns.push(s),
+                Err(EndOfStream) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(PUnsubscribe { patterns })
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("punsubscribe".as_bytes()));
+        for pattern in self.patterns {
+            frame.push_bulk(Bytes::from(pattern.into_bytes()));
+        }
+        frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{DEFAULT_SET_MAX_LISTPACK_ENTRIES, DEFAULT_SET_MAX_LISTPACK_VALUE};
+    use std::time::Duration;
+    use tokio::sync::broadcast;
+    use tokio::time;
+
+    fn new_db(channel_capacity: usize) -> Db {
+        Db::new(
+            1,
+            channel_capacity,
+            None,
+            DEFAULT_SET_MAX_LISTPACK_ENTRIES,
+            DEFAULT_SET_MAX_LISTPACK_VALUE,
+        )
+    }
+
+    // 在后台任务中对服务器一端执行 `SUBSCRIBE`，返回客户端一端的连接和关闭信号的发送端
+    fn spawn_subscribe(db: Db, channels: &[String]) -> (Connection, broadcast::Sender<()>) {
+        let (client, server) = tokio::io::duplex(4096);
+        let (notify_shutdown, notify) = broadcast::channel(1);
+        let subscribe = Subscribe::new(channels);
+        tokio::spawn(async move {
+            let mut connection: Connection = Connection::new(Box::new(server));
+            let mut shutdown = Shutdown::new(notify);
+            subscribe.apply(&db, &mut connection, &mut shutdown).await
+        });
+        (Connection::new(Box::new(client)), notify_shutdown)
+    }
+
+    // 读取一个订阅确认帧，返回频道名和当前订阅总数
+    async fn read_ack(connection: &mut Connection) -> (Bytes, i64) {
+        match connection.read_frame().await.unwrap() {
+            Some(Frame::Array(parts)) => match &parts[..] {
+                [Frame::Bulk(kind), Frame::Bulk(name), Frame::Integer(count)]
+                    if kind == "subscribe" =>
+                {
+                    (name.clone(), *count)
+                }
+                parts => panic!("unexpected ack {:?}", parts),
+            },
+            frame => panic!("unexpected frame {:?}", frame),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribe_acks_each_channel_once_in_order() {
+        let channels = (0..10).map(|i| format!("ch{}", i)).collect::<Vec<_>>();
+        let (mut client, _shutdown) = spawn_subscribe(new_db(16), &channels);
+
+        for (i, channel) in channels.iter().enumerate() {
+            let (name, count) = read_ack(&mut client).await;
+            assert_eq!(name, channel.as_str());
+            assert_eq!(count, i as i64 + 1);
+        }
+        // 确认之后不应再有多余的帧
+        let extra = time::timeout(Duration::from_millis(50), client.read_frame()).await;
+        assert!(extra.is_err());
+    }
+
+    #[tokio::test]
+    async fn lagged_subscriber_stays_connected() {
+        let db = new_db(2);
+        let (mut client, _shutdown) = spawn_subscribe(db.clone(), &["news".to_string()]);
+        read_ack(&mut client).await;
+
+        // 不让出执行权，连续发布超过频道容量的消息
+        for i in 0..20 {
+            db.publish("news", Bytes::from(format!("m{}", i)));
+        }
+
+        match client.read_frame().await.unwrap() {
+            Some(Frame::Error(msg)) => assert!(msg.starts_with(LAGGED_PREFIX), "{}", msg),
+            frame => panic!("expected lag notice, got {:?}", frame),
+        }
+        for expected in ["m18", "m19"] {
+            match client.read_frame().await.unwrap() {
+                Some(Frame::Array(parts)) => {
+                    assert!(matches!(&parts[2], Frame::Bulk(msg) if msg == expected))
+                }
+                frame => panic!("expected message, got {:?}", frame),
+            }
+        }
+
+        // 落后之后连接仍然处于订阅模式，可以继续订阅
+        client
+            .write_frame(&Subscribe::new(&["other".to_string()]).into_frame())
+            .await
+            .unwrap();
+        let (name, count) = read_ack(&mut client).await;
+        assert_eq!(name, "other");
+        assert_eq!(count, 2);
+    }
+}
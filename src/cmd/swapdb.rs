@@ -0,0 +1,49 @@
+use crate::connection::Connection;
+use crate::db::Db;
+use crate::frame::Frame;
+use crate::parse::Parse;
+use bytes::Bytes;
+use tracing::debug;
+
+#[derive(Debug)]
+pub struct SwapDb {
+    first: i64,
+    second: i64,
+}
+
+impl SwapDb {
+    pub fn new(first: i64, second: i64) -> SwapDb {
+        SwapDb { first, second }
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> crate::Result<SwapDb> {
+        let first = parse.next_signed_int()?;
+        let second = parse.next_signed_int()?;
+        Ok(SwapDb { first, second })
+    }
+
+    /// 原子地交换两个数据库的内容，已选中其中某个数据库的连接随后看到的是交换后的数据
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+        let swapped = match (usize::try_from(self.first), usize::try_from(self.second)) {
+            (Ok(first), Ok(second)) => db.swap_db(first, second),
+            _ => false,
+        };
+        let response = if swapped {
+            Frame::Simple("OK".to_string())
+        } else {
+            Frame::Error("ERR DB index is out of range".to_string())
+        };
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    pub(crate) fn into_frame(self) -> Frame {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("swapdb".as_bytes()));
+        frame.push_bulk(Bytes::from(self.first.to_string()));
+        frame.push_bulk(Bytes::from(self.second.to_string()));
+        frame
+    }
+}
@@ -1,25 +1,107 @@
 use crate::frame;
 use crate::frame::Frame;
 use bytes::{Buf, BytesMut};
+use std::fmt;
 use std::io;
 use std::io::Cursor;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
 
+/// 读缓冲区的默认初始容量
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 4 * 1024;
+
+/// 可以承载 RESP 协议的双向字节流，例如 TCP 连接或 UNIX 套接字
+pub trait Socket: AsyncRead + AsyncWrite + Unpin + Send + fmt::Debug {}
+
+impl<S: AsyncRead + AsyncWrite + Unpin + Send + fmt::Debug> Socket for S {}
+
+/// 擦除了具体类型的字节流，使同一个服务器可以同时处理不同类型的连接
+pub type BoxedSocket = Box<dyn Socket>;
+
+/// 对端在发送完一个完整的帧之前关闭了连接
+///
+/// 通常是客户端异常退出或网络中断导致的，与协议错误不同，不代表对端的实现有问题
 #[derive(Debug)]
-pub struct Connection {
-    stream: BufWriter<TcpStream>,
+pub(crate) struct ConnectionReset;
+
+impl fmt::Display for ConnectionReset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "connection reset by peer".fmt(f)
+    }
+}
+
+impl std::error::Error for ConnectionReset {}
+
+/// 判断错误是否由对端断开连接引起，包括帧不完整时断开、TCP 连接被重置和 TLS 连接未正常关闭
+pub(crate) fn is_disconnect(err: &crate::Error) -> bool {
+    if err.is::<ConnectionReset>() {
+        return true;
+    }
+    err.downcast_ref::<io::Error>().is_some_and(|err| {
+        matches!(
+            err.kind(),
+            io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::UnexpectedEof
+        )
+    })
+}
+
+#[derive(Debug)]
+pub struct Connection<S = BoxedSocket> {
+    stream: BufWriter<S>,
     buffer: BytesMut,
+    // 对端发送的单个字符串或数组允许声明的最大长度
+    max_frame_size: usize,
+    // 对端地址，用于日志和 ACL 日志，客户端一侧为空
+    pub(crate) peer_addr: String,
+    pub(crate) state: ConnectionState,
+}
+
+/// 连接级别的客户端状态，由 `CLIENT` 等命令修改，只对当前连接生效
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionState {
+    /// 当前连接的读取是否不更新键的 LRU/LFU 信息
+    pub(crate) no_touch: bool,
+    /// 通过 `HELLO` 协商的协议版本，决定回复的编码方式
+    pub(crate) protocol: Protocol,
+    /// 通过 `SELECT` 选中的数据库编号
+    pub(crate) db_index: usize,
+    /// 是否已通过 `AUTH` 认证，只在服务器配置了密码时检查
+    pub(crate) authenticated: bool,
+    /// 收到 `QUIT` 后置为 true，处理循环在回复发送后关闭连接
+    pub(crate) closing: bool,
 }
 
-impl Connection {
-    pub fn new(socket: TcpStream) -> Connection {
+/// 连接使用的 RESP 协议版本
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> Connection<S> {
+    pub fn new(socket: S) -> Connection<S> {
+        Connection::with_capacity(socket, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// 使用指定的读缓冲区初始容量创建连接，传输较大的值时可以减少读取次数和扩容
+    pub fn with_capacity(socket: S, capacity: usize) -> Connection<S> {
         Connection {
             stream: BufWriter::new(socket),
-            buffer: BytesMut::with_capacity(4096),
+            buffer: BytesMut::with_capacity(capacity),
+            max_frame_size: frame::DEFAULT_MAX_FRAME_SIZE,
+            peer_addr: String::new(),
+            state: ConnectionState::default(),
         }
     }
 
+    /// 设置对端发送的单个字符串或数组允许声明的最大长度，超过时读取会返回协议错误
+    pub fn set_max_frame_size(&mut self, max_frame_size: usize) {
+        self.max_frame_size = max_frame_size;
+    }
+
     pub async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
         loop {
             if let Some(frame) = self.parse_frame()? {
@@ -29,7 +111,7 @@ impl Connection {
                 if self.buffer.is_empty() {
                     return Ok(None);
                 } else {
-                    return Err("connection reset by peer".into());
+                    return Err(ConnectionReset.into());
                 }
             }
         }
@@ -44,7 +126,7 @@ impl Connection {
         let mut buf = Cursor::new(&self.buffer[..]);
 
         // 调用 Frame::check 方法检查缓冲区中的数据是否符合协议格式
-        match Frame::check(&mut buf) {
+        match Frame::check(&mut buf, self.max_frame_size) {
             // 如果检查通过，则解析出一个 Frame 对象
             Ok(_) => {
                 // 获取当前 Cursor 对象的位置，即已经读取的数据长度
@@ -64,25 +146,66 @@ impl Connection {
             Err(e) => Err(e.into()),
         }
     }
-    /// 将一个 `Frame` 结构体写入到 `Connection` 结构体的缓冲区中
+    /// 将一个 `Frame` 结构体写入到连接中并刷新缓冲区
     /// 如果写入成功，则返回 `Ok(())`；如果写入失败，则返回相应的错误
     pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        self.encode_frame(frame).await?;
+        // 刷新缓冲区，确保数据被实际写入到连接中
+        self.stream.flush().await
+    }
+
+    /// 依次写入多个 `Frame`，全部写入缓冲区后只刷新一次，用于客户端的流水线请求
+    pub async fn write_frames(&mut self, frames: &[Frame]) -> io::Result<()> {
+        for frame in frames {
+            self.encode_frame(frame).await?;
+        }
+        self.stream.flush().await
+    }
+
+    /// 将一个 `Frame` 结构体写入到 `Connection` 结构体的缓冲区中，不刷新缓冲区
+    async fn encode_frame(&mut self, frame: &Frame) -> io::Result<()> {
         // 根据 Frame 结构体的不同类型，进行不同的处理
         match frame {
             // 如果是数组类型，则先写入一个 '*' 字符，然后写入数组的长度，最后遍历数组中的每个元素，递归调用 write_frame 函数写入每个元素
             Frame::Array(val) => {
                 self.stream.write_u8(b'*').await?;
-                self.write_decimal(val.len() as u64).await?;
+                self.write_decimal(val.len() as i64).await?;
                 for entry in &**val {
-                    Box::pin(self.write_frame(entry)).await?;
+                    Box::pin(self.encode_frame(entry)).await?;
+                }
+            }
+            // 如果是集合类型，RESP3 下写入 '~'，RESP2 下按数组写入
+            Frame::Set(val) => {
+                let prefix = match self.state.protocol {
+                    Protocol::Resp3 => b'~',
+                    Protocol::Resp2 => b'*',
+                };
+                self.stream.write_u8(prefix).await?;
+                self.write_decimal(val.len() as i64).await?;
+                for entry in &**val {
+                    Box::pin(self.encode_frame(entry)).await?;
+                }
+            }
+            // 如果是映射类型，RESP3 下写入 '%' 和键值对数量，RESP2 下按键值交替排列的数组写入
+            Frame::Map(val) => {
+                match self.state.protocol {
+                    Protocol::Resp3 => {
+                        self.stream.write_u8(b'%').await?;
+                        self.write_decimal(val.len() as i64).await?;
+                    }
+                    Protocol::Resp2 => {
+                        self.stream.write_u8(b'*').await?;
+                        self.write_decimal(val.len() as i64 * 2).await?;
+                    }
+                }
+                for (key, value) in &**val {
+                    Box::pin(self.encode_frame(key)).await?;
+                    Box::pin(self.encode_frame(value)).await?;
                 }
             }
             // 如果是其他类型，则直接调用 write_value 函数写入值
             _ => self.write_value(frame).await?,
         }
-        // 刷新缓冲区，确保数据被实际写入到连接中
-        self.stream.flush().await?;
-        // 返回成功
         Ok(())
     }
 
@@ -109,29 +232,65 @@ impl Connection {
                 self.write_decimal(*val).await?;
             }
             // 如果是批量类型，则先写入一个 '$' 字符，然后写入批量数据的长度，接着写入批量数据的值，最后写入 "\r\n" 表示行结束
-            Frame::Bulk(val) => {
-                let len = val.len();
-                self.stream.write_u8(b'$').await?;
-                self.write_decimal(len as u64).await?;
-                self.stream.write_all(val).await?;
-                self.stream.write_all(b"\r\n").await?;
-            }
-            // 如果是空类型，则写入 "-1\r\n" 表示空值
-            Frame::Null => {
-                self.stream.write_all(b"$-1\r\n").await?;
+            Frame::Bulk(val) => self.write_bulk(val).await?,
+            // 如果是空类型，RESP3 下写入 "_\r\n"，RESP2 下写入 "$-1\r\n" 表示空值
+            Frame::Null => match self.state.protocol {
+                Protocol::Resp3 => self.stream.write_all(b"_\r\n").await?,
+                Protocol::Resp2 => self.stream.write_all(b"$-1\r\n").await?,
+            },
+            // 如果是浮点数类型，RESP3 下写入 ',' 和数值，RESP2 下按批量字符串写入
+            Frame::Double(val) => {
+                let val = Frame::format_double(*val);
+                match self.state.protocol {
+                    Protocol::Resp3 => {
+                        self.stream.write_u8(b',').await?;
+                        self.stream.write_all(val.as_bytes()).await?;
+                        self.stream.write_all(b"\r\n").await?;
+                    }
+                    Protocol::Resp2 => self.write_bulk(val.as_bytes()).await?,
+                }
             }
-            // 如果是数组类型，则不可能到达这里，因为在调用 write_value 之前已经进行了类型检查
-            Frame::Array(_) => unreachable!(),
+            // 如果是布尔类型，RESP3 下写入 "#t" 或 "#f"，RESP2 下按整数 1 或 0 写入
+            Frame::Boolean(val) => match self.state.protocol {
+                Protocol::Resp3 => {
+                    let val: &[u8] = if *val { b"#t\r\n" } else { b"#f\r\n" };
+                    self.stream.write_all(val).await?;
+                }
+                Protocol::Resp2 => {
+                    self.stream.write_u8(b':').await?;
+                    self.write_decimal(*val as i64).await?;
+                }
+            },
+            // 如果是大数类型，RESP3 下写入 '(' 和数字，RESP2 下按批量字符串写入
+            Frame::BigNumber(val) => match self.state.protocol {
+                Protocol::Resp3 => {
+                    self.stream.write_u8(b'(').await?;
+                    self.stream.write_all(val.as_bytes()).await?;
+                    self.stream.write_all(b"\r\n").await?;
+                }
+                Protocol::Resp2 => self.write_bulk(val.as_bytes()).await?,
+            },
+            // 如果是聚合类型，则不可能到达这里，因为在调用 write_value 之前已经进行了类型检查
+            Frame::Array(_) | Frame::Set(_) | Frame::Map(_) => unreachable!(),
         }
         // 返回成功
         Ok(())
     }
-    /// 将一个 `u64` 类型的十进制数写入到 `Connection` 结构体的缓冲区中
+    /// 写入一个批量字符串：先写入 '$' 和数据长度，接着写入数据，最后写入 "\r\n" 表示行结束
+    async fn write_bulk(&mut self, val: &[u8]) -> io::Result<()> {
+        self.stream.write_u8(b'$').await?;
+        self.write_decimal(val.len() as i64).await?;
+        self.stream.write_all(val).await?;
+        self.stream.write_all(b"\r\n").await?;
+        Ok(())
+    }
+
+    /// 将一个 `i64` 类型的十进制数写入到 `Connection` 结构体的缓冲区中
     /// 如果写入成功，则返回 `Ok(())`；如果写入失败，则返回相应的错误
-    async fn write_decimal(&mut self, val: u64) -> io::Result<()> {
+    async fn write_decimal(&mut self, val: i64) -> io::Result<()> {
         use std::io::Write;
-        // 创建一个长度为 12 的字节数组，用于存储十进制数的字符串表示
-        let mut buf = [0u8; 12];
+        // 创建一个长度为 20 的字节数组，足以存储任意 i64 的十进制字符串表示
+        let mut buf = [0u8; 20];
         // 创建一个 Cursor 对象，用于从字节数组中读取数据
         let mut buf = Cursor::new(&mut buf[..]);
         // 使用 write! 宏将十进制数转换为字符串，并写入到 Cursor 对象中
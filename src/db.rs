@@ -1,15 +1,24 @@
-use bytes::Bytes;
-use std::collections::{BTreeMap, HashMap};
+use crate::cmd::{BitCountUnit, SetCondition};
+use crate::frame::Frame;
+use crate::glob::glob_match;
+use bytes::{Bytes, BytesMut};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::{Notify, broadcast};
 use tokio::time;
 use tokio::time::{Duration, Instant};
 
-// 定义一个结构体，用于表示数据库实例
+// 定义一个结构体，用于表示选中了某个编号的数据库实例
 #[derive(Debug, Clone)]
 pub(crate) struct Db {
-    // 共享状态，包含数据库的所有数据和状态
+    // 当前选中的数据库编号
+    index: usize,
+    // 共享状态，包含当前数据库的所有数据和状态
     shared: Arc<Shared>,
+    // 所有数据库共用的服务器级别状态
+    global: Arc<Global>,
 }
 
 #[derive(Debug)]
@@ -18,6 +27,37 @@ struct Shared {
     background_task: Notify,
 }
 
+// 与数据库编号无关、由所有数据库共用的状态
+#[derive(Debug)]
+struct Global {
+    // 按编号排列的所有数据库
+    databases: Vec<Arc<Shared>>,
+    // 发布订阅不区分数据库，所有连接共用同一组频道
+    channels: Mutex<Channels>,
+    acl_log: Mutex<VecDeque<AclLogEntry>>,
+    // 每个命令的调用次数，按命令名索引
+    command_stats: Mutex<HashMap<String, u64>>,
+    // 当前已建立的连接数
+    connected_clients: AtomicUsize,
+    // 数据库的创建时间，即服务器的启动时间
+    started_at: Instant,
+    // 每个发布订阅频道可缓存的消息数，订阅者落后超过该数量时会丢失消息
+    channel_capacity: usize,
+    // 客户端需要通过 `AUTH` 提供的密码，`None` 表示不需要认证
+    requirepass: Option<String>,
+    // 集合使用 listpack 编码的最大成员数量和单个成员的最大字节数，超过任一限制时使用 hashtable
+    set_max_listpack_entries: usize,
+    set_max_listpack_value: usize,
+}
+
+#[derive(Debug, Default)]
+struct Channels {
+    pub_sub: HashMap<String, broadcast::Sender<Bytes>>,
+
+    // 按模式订阅的频道，消息中携带实际发布的频道名
+    pattern_subs: HashMap<String, broadcast::Sender<(String, Bytes)>>,
+}
+
 impl Shared {
     // 清除过期的键
     fn purge_expired_keys(&self) -> Option<Instant> {
@@ -61,12 +101,13 @@ impl Shared {
 struct State {
     entries: HashMap<String, Entry>,
 
-    pub_sub: HashMap<String, broadcast::Sender<Bytes>>,
-
     expirations: BTreeMap<(Instant, u64), String>,
 
     next_id: u64,
 
+    // 用于 LFU 计数器概率递增的伪随机数状态
+    rng: u64,
+
     shutdown: bool,
 }
 
@@ -77,55 +118,14 @@ impl State {
             .next()
             .map(|expiration| expiration.0)
     }
-}
-
-#[derive(Debug)]
-struct Entry {
-    id: u64,
-    data: Bytes,
-    expires_at: Option<Instant>,
-}
-
-impl Db {
-    // 创建一个新的 Db 实例
-    pub(crate) fn new() -> Db {
-        let shared = Arc::new(Shared {
-            // 初始化状态，包含一个空的哈希表、一个空的发布订阅哈希表、一个空的过期时间映射、下一个 ID 为 0，以及关闭状态为 false
-            state: Mutex::new(State {
-                entries: HashMap::new(),
-                pub_sub: HashMap::new(),
-                expirations: BTreeMap::new(),
-                next_id: 0,
-                shutdown: false,
-            }),
-            // 创建一个新的 Notify 实例，用于通知后台任务
-            background_task: Notify::new(),
-        });
-
-        // 启动一个异步任务，用于清除过期的键
-        tokio::spawn(purge_expired_tasks(shared.clone()));
-
-        // 返回一个新的 Db 实例
-        Db { shared }
-    }
-
-    // 获取指定键的值
-    pub(crate) fn get(&self, key: &str) -> Option<Bytes> {
-        // 获取互斥锁，以访问状态
-        let state = self.shared.state.lock().unwrap();
-        // 从 entries 中获取指定键的值，并返回其克隆
-        state.entries.get(key).map(|entry| entry.data.clone())
-    }
-
-    // 设置指定键的值，并可选地设置过期时间
-    pub(crate) fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
-        // 获取互斥锁，以访问状态
-        let mut state = self.shared.state.lock().unwrap();
 
+    // 插入或更新键值对，可选地设置过期时间
+    // 返回是否需要通知后台任务重新计算下一个过期时间
+    fn insert(&mut self, key: String, value: Value, expire: Option<Duration>) -> bool {
         // 获取下一个 ID
-        let id = state.next_id;
+        let id = self.next_id;
         // 增加下一个 ID
-        state.next_id += 1;
+        self.next_id += 1;
 
         // 标记是否需要通知后台任务
         let mut notify = false;
@@ -135,110 +135,1928 @@ impl Db {
             // 计算过期时间
             let when = Instant::now() + duration;
             // 检查是否需要通知后台任务
-            notify = state
+            notify = self
                 .next_expiration()
                 .map(|expiration| expiration > when)
                 .unwrap_or(true);
             // 将过期时间插入到 expirations 中
-            state.expirations.insert((when, id), key.clone());
+            self.expirations.insert((when, id), key.clone());
             // 返回过期时间
             when
         });
 
         // 插入或更新键值对
-        let prev = state.entries.insert(key, Entry {
-            // 设置 ID
-            id,
-            // 设置数据
-            data: value,
-            // 设置过期时间
-            expires_at,
-        });
+        let prev = self.entries.insert(
+            key,
+            Entry {
+                // 设置 ID
+                id,
+                // 设置数据
+                data: value,
+                // 设置过期时间
+                expires_at,
+                // 设置访问时间
+                last_access: Instant::now(),
+                // 设置 LFU 计数器的初始值
+                access_freq: LFU_INIT_VAL,
+            },
+        );
 
         // 如果之前存在该键，则从 expirations 中移除
-        if let Some(prev) = prev {
-            if let Some(when) = prev.expires_at {
-                state.expirations.remove(&(when, prev.id));
+        if let Some(prev) = prev
+            && let Some(when) = prev.expires_at
+        {
+            self.expirations.remove(&(when, prev.id));
+        }
+
+        notify
+    }
+
+    // 修改已有键的过期时间，`None` 表示永不过期
+    // 返回是否需要通知后台任务重新计算下一个过期时间
+    fn set_expiration(&mut self, key: &str, expires_at: Option<Instant>) -> bool {
+        let next = self.next_expiration();
+        let entry = match self.entries.get_mut(key) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        // 移除旧的过期时间记录
+        if let Some(when) = entry.expires_at {
+            self.expirations.remove(&(when, entry.id));
+        }
+        entry.expires_at = expires_at;
+
+        match expires_at {
+            Some(when) => {
+                self.expirations.insert((when, entry.id), key.to_string());
+                next.map(|expiration| expiration > when).unwrap_or(true)
             }
+            None => false,
         }
+    }
 
-        // 释放互斥锁
-        drop(state);
+    // 将键从 `src` 移动到 `dst`，覆盖 `dst` 原有的值，并保留过期时间
+    // `src` 不存在或已过期时返回错误
+    fn rename(&mut self, src: &str, dst: &str, now: Instant) -> crate::Result<()> {
+        let entry = self
+            .remove(src)
+            .filter(|entry| !entry.is_expired(now))
+            .ok_or("ERR no such key")?;
+        self.remove(dst);
 
-        // 如果需要通知后台任务，则进行通知
-        if notify {
-            #[allow(clippy::let_underscore_future)]
-            let _ = self.shared.background_task.notified();
+        // 过期时间记录中保存的是键名，需要改为指向新的键
+        if let Some(when) = entry.expires_at {
+            self.expirations.insert((when, entry.id), dst.to_string());
         }
+        self.entries.insert(dst.to_string(), entry);
+        Ok(())
     }
 
-    // 订阅指定键的发布订阅频道
-    pub(crate) fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
-        use std::collections::hash_map::Entry;
+    // 移除指定的键，同时清理其在 expirations 中的记录
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        if let Some(when) = entry.expires_at {
+            self.expirations.remove(&(when, entry.id));
+        }
+        Some(entry)
+    }
+}
 
-        // 获取互斥锁，以访问状态
-        let mut state = self.shared.state.lock().unwrap();
+#[derive(Debug)]
+struct Entry {
+    id: u64,
+    data: Value,
+    expires_at: Option<Instant>,
+    last_access: Instant,
+    access_freq: u8,
+}
 
-        // 检查键是否已经存在于 pub_sub 中
-        match state.pub_sub.entry(key) {
-            // 如果键已经存在，则返回订阅者
-            Entry::Occupied(e) => e.get().subscribe(),
-            // 如果键不存在，则创建一个新的发布订阅频道，并返回订阅者
-            Entry::Vacant(e) => {
-                let (tx, rx) = broadcast::channel(1024);
-                e.insert(tx);
-                rx
+impl Entry {
+    // 判断键是否已经过期；过期的键在被后台任务清除之前仍然留在 entries 中
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|when| when <= now)
+    }
+
+    // 值的类型名称，与 `TYPE` 命令的返回值一致
+    fn type_name(&self) -> &'static str {
+        match self.data {
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Hash(_) => "hash",
+            Value::Set(_) => "set",
+        }
+    }
+
+    // 记录一次访问：先按经过的时间衰减 LFU 计数器，再按对数概率递增，最后更新访问时间
+    fn touch(&mut self, rng: &mut u64) {
+        let counter = self.lfu_decayed();
+        self.access_freq = lfu_log_incr(counter, rng);
+        self.last_access = Instant::now();
+    }
+
+    // 计算衰减后的 LFU 计数器：自上次访问以来每经过一个衰减周期减 1
+    fn lfu_decayed(&self) -> u8 {
+        let periods = self.last_access.elapsed().as_secs() / LFU_DECAY_TIME.as_secs();
+        self.access_freq
+            .saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+}
+
+// 对类型不匹配的键执行操作时返回的错误
+const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+// 列表使用 listpack 编码的最大元素数量和单个元素的最大字节数，超过任一限制时使用 quicklist
+const LIST_MAX_LISTPACK_ENTRIES: usize = 128;
+const LIST_MAX_LISTPACK_VALUE: usize = 64;
+
+// 哈希使用 listpack 编码的最大字段数量和字段或值的最大字节数，超过任一限制时使用 hashtable
+const HASH_MAX_LISTPACK_ENTRIES: usize = 128;
+const HASH_MAX_LISTPACK_VALUE: usize = 64;
+
+// 集合的成员都是整数时使用 intset 编码的最大成员数量
+const SET_MAX_INTSET_ENTRIES: usize = 512;
+
+// 键对应的值，每个变体对应 `TYPE` 命令的一种返回值
+#[derive(Debug)]
+enum Value {
+    String(Bytes),
+    List(VecDeque<Bytes>),
+    Hash(HashMap<Bytes, Bytes>),
+    Set(HashSet<Bytes>),
+}
+
+impl Value {
+    // 以字符串的形式访问值，值不是字符串时返回 WRONGTYPE 错误
+    fn as_string(&self) -> crate::Result<&Bytes> {
+        match self {
+            Value::String(data) => Ok(data),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 以列表的形式访问值，值不是列表时返回 WRONGTYPE 错误
+    fn as_list(&self) -> crate::Result<&VecDeque<Bytes>> {
+        match self {
+            Value::List(list) => Ok(list),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 以列表的形式修改值，值不是列表时返回 WRONGTYPE 错误
+    fn as_list_mut(&mut self) -> crate::Result<&mut VecDeque<Bytes>> {
+        match self {
+            Value::List(list) => Ok(list),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 以哈希的形式访问值，值不是哈希时返回 WRONGTYPE 错误
+    fn as_hash(&self) -> crate::Result<&HashMap<Bytes, Bytes>> {
+        match self {
+            Value::Hash(hash) => Ok(hash),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 以哈希的形式修改值，值不是哈希时返回 WRONGTYPE 错误
+    fn as_hash_mut(&mut self) -> crate::Result<&mut HashMap<Bytes, Bytes>> {
+        match self {
+            Value::Hash(hash) => Ok(hash),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 以集合的形式访问值，值不是集合时返回 WRONGTYPE 错误
+    fn as_set(&self) -> crate::Result<&HashSet<Bytes>> {
+        match self {
+            Value::Set(set) => Ok(set),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 以集合的形式修改值，值不是集合时返回 WRONGTYPE 错误
+    fn as_set_mut(&mut self) -> crate::Result<&mut HashSet<Bytes>> {
+        match self {
+            Value::Set(set) => Ok(set),
+            _ => Err(WRONGTYPE.into()),
+        }
+    }
+
+    // 按照 Redis 的规则推断值使用的编码方式，集合的 listpack 限制来自服务器配置
+    fn encoding(
+        &self,
+        set_max_listpack_entries: usize,
+        set_max_listpack_value: usize,
+    ) -> &'static str {
+        match self {
+            Value::String(data) => Db::string_encoding(data),
+            Value::List(list)
+                if list.len() <= LIST_MAX_LISTPACK_ENTRIES
+                    && list
+                        .iter()
+                        .all(|item| item.len() <= LIST_MAX_LISTPACK_VALUE) =>
+            {
+                "listpack"
+            }
+            Value::List(_) => "quicklist",
+            Value::Hash(hash)
+                if hash.len() <= HASH_MAX_LISTPACK_ENTRIES
+                    && hash.iter().all(|(field, value)| {
+                        field.len() <= HASH_MAX_LISTPACK_VALUE
+                            && value.len() <= HASH_MAX_LISTPACK_VALUE
+                    }) =>
+            {
+                "listpack"
             }
+            Value::Hash(_) => "hashtable",
+            Value::Set(set)
+                if set.len() <= SET_MAX_INTSET_ENTRIES
+                    && set.iter().all(|member| string_as_int(member).is_some()) =>
+            {
+                "intset"
+            }
+            Value::Set(set)
+                if set.len() <= set_max_listpack_entries
+                    && set
+                        .iter()
+                        .all(|member| member.len() <= set_max_listpack_value) =>
+            {
+                "listpack"
+            }
+            Value::Set(_) => "hashtable",
         }
     }
 
-    // 发布指定键的值到发布订阅频道
-    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
+    // 近似计算值按简单 RDB 格式序列化后的长度，列表、哈希和集合为元素数量前缀加上每个元素的长度
+    fn serialized_len(&self) -> u64 {
+        match self {
+            Value::String(data) => rdb_string_len(data),
+            Value::List(list) => {
+                rdb_len_prefix(list.len() as u64) + list.iter().map(rdb_string_len).sum::<u64>()
+            }
+            Value::Hash(hash) => {
+                rdb_len_prefix(hash.len() as u64)
+                    + hash
+                        .iter()
+                        .map(|(field, value)| rdb_string_len(field) + rdb_string_len(value))
+                        .sum::<u64>()
+            }
+            Value::Set(set) => {
+                rdb_len_prefix(set.len() as u64) + set.iter().map(rdb_string_len).sum::<u64>()
+            }
+        }
+    }
+}
+
+// `DEBUG OBJECT` 返回的诊断信息
+#[derive(Debug)]
+pub(crate) struct DebugObjectInfo {
+    pub(crate) addr: usize,
+    pub(crate) refcount: u64,
+    pub(crate) encoding: &'static str,
+    pub(crate) serialized_len: u64,
+    pub(crate) lru: u32,
+    pub(crate) lru_idle: u64,
+}
+
+// ACL 日志最多保留的条目数
+const ACL_LOG_MAX_LEN: usize = 128;
+
+// 服务器只有一个用户，与 Redis 的默认用户同名
+pub(crate) const DEFAULT_USER: &str = "default";
+
+// ACL 拒绝访问的原因：认证失败，或未认证的连接执行了需要认证的命令
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AclDenyReason {
+    Auth,
+    Command,
+}
+
+impl AclDenyReason {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            AclDenyReason::Auth => "auth",
+            AclDenyReason::Command => "command",
+        }
+    }
+}
+
+// 一条 ACL 违规记录
+#[derive(Debug, Clone)]
+pub(crate) struct AclLogEntry {
+    pub(crate) timestamp: SystemTime,
+    pub(crate) reason: AclDenyReason,
+    pub(crate) username: String,
+    pub(crate) client_addr: String,
+    pub(crate) call: String,
+}
+
+impl Db {
+    // 创建 `databases` 个数据库，返回选中 0 号数据库的实例
+    // `channel_capacity` 为每个发布订阅频道的缓冲容量，`requirepass` 为客户端认证使用的密码
+    // `set_max_listpack_entries` 和 `set_max_listpack_value` 决定集合何时从 listpack 转为 hashtable 编码
+    pub(crate) fn new(
+        databases: usize,
+        channel_capacity: usize,
+        requirepass: Option<String>,
+        set_max_listpack_entries: usize,
+        set_max_listpack_value: usize,
+    ) -> Db {
+        let databases = (0..databases.max(1))
+            .map(|_| {
+                let shared = Arc::new(Shared {
+                    // 初始化状态，包含一个空的哈希表、一个空的过期时间映射、下一个 ID 为 0，以及关闭状态为 false
+                    state: Mutex::new(State {
+                        entries: HashMap::new(),
+                        expirations: BTreeMap::new(),
+                        next_id: 0,
+                        rng: lfu_seed(),
+                        shutdown: false,
+                    }),
+                    // 创建一个新的 Notify 实例，用于通知后台任务
+                    background_task: Notify::new(),
+                });
+
+                // 每个数据库各自启动一个异步任务，用于清除过期的键
+                tokio::spawn(purge_expired_tasks(shared.clone()));
+                shared
+            })
+            .collect::<Vec<_>>();
+
+        let global = Arc::new(Global {
+            databases,
+            channels: Mutex::new(Channels::default()),
+            // 创建一个空的 ACL 日志
+            acl_log: Mutex::new(VecDeque::new()),
+            // 创建一个空的命令统计表
+            command_stats: Mutex::new(HashMap::new()),
+            connected_clients: AtomicUsize::new(0),
+            started_at: Instant::now(),
+            // 容量为 0 的广播通道会在创建时 panic，与数据库数量一样至少为 1
+            channel_capacity: channel_capacity.max(1),
+            requirepass,
+            set_max_listpack_entries,
+            set_max_listpack_value,
+        });
+
+        Db {
+            index: 0,
+            shared: global.databases[0].clone(),
+            global,
+        }
+    }
+
+    // 返回选中 `index` 号数据库的实例，编号超出范围时返回 None
+    pub(crate) fn select(&self, index: usize) -> Option<Db> {
+        self.global.databases.get(index).map(|shared| Db {
+            index,
+            shared: shared.clone(),
+            global: self.global.clone(),
+        })
+    }
+
+    // 数据库的数量
+    pub(crate) fn databases(&self) -> usize {
+        self.global.databases.len()
+    }
+
+    // 将键连同过期时间移动到 `index` 号数据库，返回是否进行了移动
+    // 键不存在或目标数据库中已存在同名的键时不做任何修改
+    pub(crate) fn move_key(&self, key: &str, index: usize) -> crate::Result<bool> {
+        let target = self
+            .global
+            .databases
+            .get(index)
+            .ok_or("ERR DB index is out of range")?;
+        if index == self.index {
+            return Err("ERR source and destination objects are the same".into());
+        }
+
+        // 按编号顺序加锁，避免两个方向相反的 MOVE 互相等待
+        let (mut src, mut dst) = if self.index < index {
+            let src = self.shared.state.lock().unwrap();
+            (src, target.state.lock().unwrap())
+        } else {
+            let dst = target.state.lock().unwrap();
+            (self.shared.state.lock().unwrap(), dst)
+        };
+        let now = Instant::now();
+
+        if src
+            .entries
+            .get(key)
+            .is_none_or(|entry| entry.is_expired(now))
+            || dst
+                .entries
+                .get(key)
+                .is_some_and(|entry| !entry.is_expired(now))
+        {
+            return Ok(false);
+        }
+
+        // 目标数据库中可能残留尚未清除的同名过期键
+        dst.remove(key);
+        let mut entry = src.remove(key).unwrap();
+
+        // ID 只在各自的数据库内唯一，需要在目标数据库中重新分配
+        entry.id = dst.next_id;
+        dst.next_id += 1;
+        let mut notify = false;
+        if let Some(when) = entry.expires_at {
+            notify = dst
+                .next_expiration()
+                .map(|expiration| expiration > when)
+                .unwrap_or(true);
+            dst.expirations.insert((when, entry.id), key.to_string());
+        }
+        dst.entries.insert(key.to_string(), entry);
+        drop(src);
+        drop(dst);
+
+        // 键带来了更早的过期时间时，通知目标数据库的后台任务
+        if notify {
+            target.background_task.notify_one();
+        }
+        Ok(true)
+    }
+
+    // 交换两个数据库的全部键，编号超出范围时返回 false
+    // 发布订阅由所有数据库共用，不受影响
+    pub(crate) fn swap_db(&self, first: usize, second: usize) -> bool {
+        let databases = &self.global.databases;
+        if first >= databases.len() || second >= databases.len() {
+            return false;
+        }
+        if first == second {
+            return true;
+        }
+
+        // 按编号顺序加锁，避免两个方向相反的 SWAPDB 互相等待
+        let (low, high) = (first.min(second), first.max(second));
+        let mut low_state = databases[low].state.lock().unwrap();
+        let mut high_state = databases[high].state.lock().unwrap();
+        // 过期时间映射中的 ID 与 entries 对应，三者需要一起交换
+        std::mem::swap(&mut low_state.entries, &mut high_state.entries);
+        std::mem::swap(&mut low_state.expirations, &mut high_state.expirations);
+        std::mem::swap(&mut low_state.next_id, &mut high_state.next_id);
+        drop(low_state);
+        drop(high_state);
+
+        // 两个数据库最早的过期时间都可能改变，通知各自的后台任务重新计算
+        databases[low].background_task.notify_one();
+        databases[high].background_task.notify_one();
+        true
+    }
+
+    // 获取指定键的值
+    // `touch` 为 false 时不更新键的访问时间和访问频率
+    // 值不是字符串时返回 WRONGTYPE 错误
+    pub(crate) fn get(&self, key: &str, touch: bool) -> crate::Result<Option<Bytes>> {
         // 获取互斥锁，以访问状态
+        let mut state = self.shared.state.lock().unwrap();
+        let state = &mut *state;
+        // 从 entries 中获取指定键的值，按需更新访问信息，并返回其克隆
+        let entry = match state.entries.get_mut(key) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let data = entry.data.as_string()?.clone();
+        if touch {
+            entry.touch(&mut state.rng);
+        }
+        Ok(Some(data))
+    }
+
+    // 在一次加锁内批量获取多个键的值，结果与 `keys` 的顺序一致
+    // 不存在、已过期或值不是字符串的键对应 None
+    pub(crate) fn mget(&self, keys: &[String], touch: bool) -> Vec<Option<Bytes>> {
+        let mut state = self.shared.state.lock().unwrap();
+        let state = &mut *state;
+        let now = Instant::now();
+        keys.iter()
+            .map(|key| {
+                let entry = state
+                    .entries
+                    .get_mut(key)
+                    .filter(|entry| !entry.is_expired(now))?;
+                let data = entry.data.as_string().ok()?.clone();
+                if touch {
+                    entry.touch(&mut state.rng);
+                }
+                Some(data)
+            })
+            .collect()
+    }
+
+    // 清空所有的键及其过期时间，发布订阅频道不受影响
+    // 后台任务醒来时会发现 expirations 为空，随后等待下一次通知，因此无需通知
+    pub(crate) fn flush(&self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.entries.clear();
+        state.expirations.clear();
+    }
+
+    // 获取指定键的值的类型名称，键不存在或已过期时返回 None
+    pub(crate) fn type_of(&self, key: &str) -> Option<&'static str> {
         let state = self.shared.state.lock().unwrap();
-        // 从 pub_sub 中获取指定键的发送者，并发送值
+        let now = Instant::now();
         state
-            .pub_sub
+            .entries
             .get(key)
-            .map(|tx| tx.send(value).unwrap_or(0))
-            .unwrap_or(0)
+            .filter(|entry| !entry.is_expired(now))
+            .map(Entry::type_name)
     }
-}
 
-// 为 Db 结构体实现 Drop 特征，用于在实例被销毁时执行清理操作
-impl Drop for Db {
-    fn drop(&mut self) {
-        // 检查当前共享实例的强引用计数是否为 2
-        if Arc::strong_count(&self.shared) == 2 {
-            // 获取互斥锁，以访问状态
-            let mut state = self.shared.state.lock().unwrap();
-            // 设置状态为关闭
-            state.shutdown = true;
-            // 释放互斥锁
-            drop(state);
-            // 通知后台任务
-            #[allow(clippy::let_underscore_future)]
-            let _ = self.shared.background_task.notified();
+    // 返回数据库中未过期的键的数量
+    pub(crate) fn dbsize(&self) -> u64 {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        state
+            .entries
+            .values()
+            .filter(|entry| !entry.is_expired(now))
+            .count() as u64
+    }
+
+    // 返回设置了过期时间且尚未过期的键的数量
+    pub(crate) fn expires(&self) -> u64 {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        state
+            .entries
+            .values()
+            .filter(|entry| entry.expires_at.is_some() && !entry.is_expired(now))
+            .count() as u64
+    }
+
+    // 返回所有与 glob 模式匹配且未过期的键名，需要遍历整个数据库
+    pub(crate) fn keys(&self, pattern: &str) -> Vec<String> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        state
+            .entries
+            .iter()
+            .filter(|(key, entry)| {
+                !entry.is_expired(now) && glob_match(pattern.as_bytes(), key.as_bytes())
+            })
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    // 从游标处开始遍历最多 `count` 个键，返回下一次遍历的游标和其中与模式匹配的键，游标为 0 表示遍历结束
+    // 游标是按键名排序后的位置，遍历期间增删键可能导致个别键被跳过或重复返回
+    pub(crate) fn scan(
+        &self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: usize,
+    ) -> (u64, Vec<String>) {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let mut keys: Vec<&String> = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| !entry.is_expired(now))
+            .map(|(key, _)| key)
+            .collect();
+        let start = match usize::try_from(cursor) {
+            Ok(start) if start < keys.len() => start,
+            _ => return (0, vec![]),
+        };
+        let end = start.saturating_add(count).min(keys.len());
+
+        // 只需要排序后位于 [start, end) 的键：先划分出前 start 个最小的键，再在剩余的键中选出最小的若干个排序
+        keys.select_nth_unstable(start);
+        let window = &mut keys[start..];
+        let len = end - start;
+        if len < window.len() {
+            window.select_nth_unstable(len);
         }
+        window[..len].sort_unstable();
+
+        let next = if end == keys.len() { 0 } else { end as u64 };
+        let keys = keys[start..end]
+            .iter()
+            .filter(|key| {
+                pattern.is_none_or(|pattern| glob_match(pattern.as_bytes(), key.as_bytes()))
+            })
+            .map(|key| key.to_string())
+            .collect();
+        (next, keys)
     }
-}
 
-// 定义一个异步函数，用于清除过期的任务
-async fn purge_expired_tasks(shared: Arc<Shared>) {
-    // 只要数据库未关闭，就一直循环执行清除任务
-    while !shared.is_shutdown() {
-        // 调用 `purge_expired_keys` 方法获取下一个过期时间
-        if let Some(when) = shared.purge_expired_keys() {
-            // 使用 `tokio::select!` 宏等待 `sleep_until` 或 `background_task` 通知
-            tokio::select! {
-                // 等待直到指定的时间 `when`
-                _=time::sleep_until(when)=>{}
-                // 或者等待 `background_task` 被通知
-                _=shared.background_task.notified()=>{}
-            }
+    // 获取指定键的值的字节长度，键不存在或已过期时返回 0
+    pub(crate) fn strlen(&self, key: &str) -> crate::Result<u64> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Ok(entry.data.as_string()?.len() as u64),
+            _ => Ok(0),
+        }
+    }
+
+    // 获取指定键的值在 [start, end] 范围内的子串，负数索引从末尾开始计算
+    // 键不存在或已过期时返回空串
+    pub(crate) fn getrange(&self, key: &str, start: i64, end: i64) -> crate::Result<Bytes> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        let data = match state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+        {
+            Some(entry) => entry.data.as_string()?,
+            None => return Ok(Bytes::new()),
+        };
+
+        let len = data.len() as i64;
+        let start = if start < 0 {
+            (len + start).max(0)
         } else {
-            // 如果没有找到过期时间，则等待 `background_task` 通知
-            shared.background_task.notified().await;
+            start
+        };
+        let end = if end < 0 {
+            (len + end).max(0)
+        } else {
+            end.min(len - 1)
+        };
+        if len == 0 || start > end {
+            return Ok(Bytes::new());
+        }
+        Ok(data.slice(start as usize..=end as usize))
+    }
+
+    // 统计指定键的值中被设置为 1 的位数，可选地只统计 [start, end] 范围
+    // 范围的单位可以是字节或位，负数索引从末尾开始计算；位的编号从每个字节的最高位开始
+    pub(crate) fn bitcount(
+        &self,
+        key: &str,
+        range: Option<(i64, i64, BitCountUnit)>,
+    ) -> crate::Result<u64> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        let data = match state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+        {
+            Some(entry) => &entry.data.as_string()?[..],
+            None => return Ok(0),
+        };
+
+        let (start, end, unit) = match range {
+            Some(range) => range,
+            None => return Ok(data.iter().map(|b| b.count_ones() as u64).sum()),
+        };
+
+        let len = match unit {
+            BitCountUnit::Byte => data.len() as i64,
+            BitCountUnit::Bit => data.len() as i64 * 8,
+        };
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 {
+            (len + end).max(0)
+        } else {
+            end.min(len - 1)
+        };
+        if len == 0 || start > end {
+            return Ok(0);
+        }
+
+        Ok(match unit {
+            BitCountUnit::Byte => data[start as usize..=end as usize]
+                .iter()
+                .map(|b| b.count_ones() as u64)
+                .sum(),
+            BitCountUnit::Bit => {
+                let (first, last) = ((start / 8) as usize, (end / 8) as usize);
+                // 屏蔽首字节中 start 之前的位，以及末字节中 end 之后的位
+                let first_mask = 0xffu8 >> (start % 8);
+                let last_mask = 0xffu8 << (7 - end % 8);
+                data[first..=last]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &b)| {
+                        let mut b = b;
+                        if i == 0 {
+                            b &= first_mask;
+                        }
+                        if first + i == last {
+                            b &= last_mask;
+                        }
+                        b.count_ones() as u64
+                    })
+                    .sum()
+            }
+        })
+    }
+
+    // 获取指定键的底层诊断信息，键不存在或已过期时返回 None
+    pub(crate) fn debug_object(&self, key: &str) -> Option<DebugObjectInfo> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        let entry = state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))?;
+
+        // 与 Redis 的 LRU 时钟一致：以秒为单位的 unix 时间，截断为 24 位
+        let idle = entry.last_access.elapsed();
+        let lru = SystemTime::now()
+            .checked_sub(idle)
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map(|since| (since.as_secs() & LRU_CLOCK_MAX) as u32)
+            .unwrap_or(0);
+
+        Some(DebugObjectInfo {
+            addr: &entry.data as *const Value as usize,
+            refcount: 1,
+            encoding: self.encoding(&entry.data),
+            serialized_len: entry.data.serialized_len(),
+            lru,
+            lru_idle: idle.as_secs(),
+        })
+    }
+
+    // 设置指定键的值，并可选地设置过期时间，返回是否进行了写入
+    // `condition` 不满足时不做任何修改；`keep_ttl` 为 true 时保留键原有的过期时间
+    pub(crate) fn set(
+        &self,
+        key: String,
+        value: Bytes,
+        expire: Option<Duration>,
+        condition: Option<SetCondition>,
+        keep_ttl: bool,
+    ) -> bool {
+        // 获取互斥锁，以访问状态
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        // 已过期但尚未清除的键视为不存在
+        let prev_expires_at = match state.entries.get(&key) {
+            Some(entry) if !entry.is_expired(now) => Some(entry.expires_at),
+            _ => None,
+        };
+        match condition {
+            Some(SetCondition::NotExists) if prev_expires_at.is_some() => return false,
+            Some(SetCondition::Exists) if prev_expires_at.is_none() => return false,
+            _ => {}
+        }
+
+        // 插入或更新键值对，并记录是否需要通知后台任务
+        let mut notify = state.insert(key.clone(), Value::String(value), expire);
+        if keep_ttl && let Some(Some(when)) = prev_expires_at {
+            notify = state.set_expiration(&key, Some(when));
+        }
+
+        // 释放互斥锁
+        drop(state);
+
+        // 如果需要通知后台任务，则进行通知
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+        true
+    }
+
+    // 删除指定的键，返回实际删除的键数量，已过期但尚未清除的键不计入
+    pub(crate) fn del(&self, keys: &[String]) -> u64 {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        keys.iter()
+            .filter_map(|key| state.remove(key))
+            .filter(|entry| !entry.is_expired(now))
+            .count() as u64
+    }
+
+    // 统计指定的键中存在的数量，重复的键会被重复计数，已过期但尚未清除的键视为不存在
+    pub(crate) fn exists(&self, keys: &[String]) -> u64 {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        keys.iter()
+            .filter(|key| {
+                state
+                    .entries
+                    .get(key.as_str())
+                    .is_some_and(|entry| !entry.is_expired(now))
+            })
+            .count() as u64
+    }
+
+    // 将指定键中存储的整数加上 `delta` 并返回新值，键不存在时视为 0
+    // 更新已有的键时保留其过期时间
+    pub(crate) fn incr_by(&self, key: &str, delta: i64) -> crate::Result<i64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let current = match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Some(
+                string_as_int(entry.data.as_string()?)
+                    .ok_or("ERR value is not an integer or out of range")?,
+            ),
+            _ => None,
+        };
+        let value = current
+            .unwrap_or(0)
+            .checked_add(delta)
+            .ok_or("ERR increment or decrement would overflow")?;
+        let data = Value::String(Bytes::from(value.to_string()));
+
+        match current {
+            Some(_) => {
+                if let Some(entry) = state.entries.get_mut(key) {
+                    entry.data = data;
+                }
+            }
+            None => {
+                state.insert(key.to_string(), data, None);
+            }
+        }
+        Ok(value)
+    }
+
+    // 将键中的数值加上浮点数 `delta` 并返回新值，过期时间保持不变
+    pub(crate) fn incr_by_float(&self, key: &str, delta: f64) -> crate::Result<f64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let current = match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Some(
+                string_as_float(entry.data.as_string()?).ok_or("ERR value is not a valid float")?,
+            ),
+            _ => None,
+        };
+        let value = current.unwrap_or(0.0) + delta;
+        if !value.is_finite() {
+            return Err("ERR increment would produce NaN or Infinity".into());
+        }
+        let data = Value::String(Bytes::from(Frame::format_double(value)));
+
+        match current {
+            Some(_) => {
+                if let Some(entry) = state.entries.get_mut(key) {
+                    entry.data = data;
+                }
+            }
+            None => {
+                state.insert(key.to_string(), data, None);
+            }
+        }
+        Ok(value)
+    }
+
+    // 为已存在的键设置过期时间，返回该键是否存在
+    pub(crate) fn expire(&self, key: &str, ttl: Duration) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(key)
+            .is_none_or(|entry| entry.is_expired(now))
+        {
+            return false;
+        }
+        let notify = state.set_expiration(key, Some(now + ttl));
+
+        // 释放互斥锁
+        drop(state);
+
+        // 如果需要通知后台任务，则进行通知
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+        true
+    }
+
+    // 移除指定键的过期时间，返回是否确实移除了过期时间
+    // 键不存在或没有设置过期时间时返回 false
+    pub(crate) fn persist(&self, key: &str) -> bool {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(key)
+            .is_none_or(|entry| entry.is_expired(now) || entry.expires_at.is_none())
+        {
+            return false;
+        }
+        // 后台任务醒来后会从 expirations 中重新读取下一个过期时间，这里无需通知
+        state.set_expiration(key, None);
+        true
+    }
+
+    // 获取指定键剩余的生存时间（秒，四舍五入），键不存在时返回 None，没有设置过期时间时返回 -1
+    pub(crate) fn ttl(&self, key: &str) -> Option<i64> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        let entry = state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))?;
+        Some(match entry.expires_at {
+            Some(when) => ((when - now).as_millis() as i64 + 500) / 1000,
+            None => -1,
+        })
+    }
+
+    // 将数据追加到指定键的值之后，键不存在时创建该键，返回追加后值的字节长度
+    // 追加到已有的键时保留其过期时间
+    pub(crate) fn append(&self, key: &str, data: &[u8]) -> crate::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        match state.entries.get_mut(key) {
+            Some(entry) if !entry.is_expired(now) => {
+                let prev = entry.data.as_string()?;
+                let mut buf = BytesMut::with_capacity(prev.len() + data.len());
+                buf.extend_from_slice(prev);
+                buf.extend_from_slice(data);
+                let len = buf.len() as u64;
+                entry.data = Value::String(buf.freeze());
+                Ok(len)
+            }
+            _ => {
+                let value = Value::String(Bytes::copy_from_slice(data));
+                state.insert(key.to_string(), value, None);
+                Ok(data.len() as u64)
+            }
+        }
+    }
+
+    // 删除指定的键并返回其值，键不存在或已过期时返回 None
+    // 值不是字符串时返回 WRONGTYPE 错误，且不删除该键
+    pub(crate) fn getdel(&self, key: &str) -> crate::Result<Option<Bytes>> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        let data = match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => entry.data.as_string()?.clone(),
+            _ => return Ok(None),
+        };
+        state.remove(key);
+        Ok(Some(data))
+    }
+
+    // 设置指定键的值并返回旧值，键不存在或已过期时返回 None
+    // 与 Redis 一致，会清除键原有的过期时间
+    // 旧值不是字符串时返回 WRONGTYPE 错误，且不做任何修改
+    pub(crate) fn getset(&self, key: String, value: Bytes) -> crate::Result<Option<Bytes>> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        let prev = match state.entries.get(&key) {
+            Some(entry) if !entry.is_expired(now) => Some(entry.data.as_string()?.clone()),
+            _ => None,
+        };
+        state.insert(key, Value::String(value), None);
+        Ok(prev)
+    }
+
+    // 仅当键不存在时设置其值，返回是否进行了设置；已过期但尚未清除的键视为不存在
+    pub(crate) fn set_nx(&self, key: String, value: Bytes) -> bool {
+        // 获取互斥锁，在同一把锁内完成检查和写入
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(&key)
+            .is_some_and(|entry| !entry.is_expired(now))
+        {
+            return false;
+        }
+        state.insert(key, Value::String(value), None);
+        true
+    }
+
+    // 将键从 `src` 重命名为 `dst`，覆盖 `dst` 原有的值
+    pub(crate) fn rename(&self, src: &str, dst: &str) -> crate::Result<()> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.rename(src, dst, Instant::now())
+    }
+
+    // 仅当 `dst` 不存在时将键从 `src` 重命名为 `dst`，返回是否进行了重命名
+    pub(crate) fn rename_nx(&self, src: &str, dst: &str) -> crate::Result<bool> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(src)
+            .is_none_or(|entry| entry.is_expired(now))
+        {
+            return Err("ERR no such key".into());
+        }
+        if state
+            .entries
+            .get(dst)
+            .is_some_and(|entry| !entry.is_expired(now))
+        {
+            return Ok(false);
+        }
+        state.rename(src, dst, now)?;
+        Ok(true)
+    }
+
+    // 在一次加锁内设置多个键，覆盖已有的值并清除其过期时间
+    pub(crate) fn mset(&self, pairs: Vec<(String, Bytes)>) {
+        let mut state = self.shared.state.lock().unwrap();
+        for (key, value) in pairs {
+            state.insert(key, Value::String(value), None);
+        }
+    }
+
+    // 仅当所有键都不存在时才一次性设置多个键，返回是否进行了设置
+    // 与 `set_nx` 一致，已过期但尚未清除的键视为不存在
+    pub(crate) fn msetnx(&self, pairs: Vec<(String, Bytes)>) -> bool {
+        // 获取互斥锁，在同一把锁内完成检查和写入
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        // 只要有一个键已经存在，就不做任何修改
+        if pairs.iter().any(|(key, _)| {
+            state
+                .entries
+                .get(key)
+                .is_some_and(|entry| !entry.is_expired(now))
+        }) {
+            return false;
+        }
+
+        for (key, value) in pairs {
+            state.insert(key, Value::String(value), None);
+        }
+        true
+    }
+
+    // 将元素依次插入到列表的头部或尾部，键不存在时创建列表，返回插入后列表的长度
+    // 与 Redis 一致，插入到头部的多个元素在列表中的顺序与参数顺序相反
+    pub(crate) fn push(&self, key: &str, values: Vec<Bytes>, front: bool) -> crate::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(key)
+            .is_none_or(|entry| entry.is_expired(now))
+        {
+            state.insert(key.to_string(), Value::List(VecDeque::new()), None);
+        }
+        let list = match state.entries.get_mut(key) {
+            Some(entry) => entry.data.as_list_mut()?,
+            None => return Ok(0),
+        };
+        for value in values {
+            if front {
+                list.push_front(value);
+            } else {
+                list.push_back(value);
+            }
+        }
+        Ok(list.len() as u64)
+    }
+
+    // 返回列表的长度，键不存在时返回 0
+    pub(crate) fn llen(&self, key: &str) -> crate::Result<u64> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Ok(entry.data.as_list()?.len() as u64),
+            _ => Ok(0),
+        }
+    }
+
+    // 返回列表中指定位置的元素，负数索引从末尾开始计算，越界时返回 None
+    pub(crate) fn lindex(&self, key: &str, index: i64) -> crate::Result<Option<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let list = match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => entry.data.as_list()?,
+            _ => return Ok(None),
+        };
+        let index = if index < 0 {
+            list.len() as i64 + index
+        } else {
+            index
+        };
+        Ok(usize::try_from(index)
+            .ok()
+            .and_then(|index| list.get(index))
+            .cloned())
+    }
+
+    // 从列表的头部或尾部移除并返回最多 `count` 个元素，键不存在时返回 None
+    // 列表被取空后删除该键
+    pub(crate) fn pop(
+        &self,
+        key: &str,
+        count: usize,
+        front: bool,
+    ) -> crate::Result<Option<Vec<Bytes>>> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let list = match state.entries.get_mut(key) {
+            Some(entry) if !entry.is_expired(now) => entry.data.as_list_mut()?,
+            _ => return Ok(None),
+        };
+        let count = count.min(list.len());
+        let values = if front {
+            list.drain(..count).collect()
+        } else {
+            list.drain(list.len() - count..).rev().collect()
+        };
+        if list.is_empty() {
+            state.remove(key);
+        }
+        Ok(Some(values))
+    }
+
+    // 返回列表中 [start, stop] 范围内的元素，负数索引从末尾开始计算
+    pub(crate) fn lrange(&self, key: &str, start: i64, stop: i64) -> crate::Result<Vec<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let list = match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => entry.data.as_list()?,
+            _ => return Ok(vec![]),
+        };
+        Ok(match list_range(list.len(), start, stop) {
+            Some((start, stop)) => list.range(start..=stop).cloned().collect(),
+            None => vec![],
+        })
+    }
+
+    // 设置哈希中的字段，键不存在时创建哈希，返回新增的字段数量
+    pub(crate) fn hset(&self, key: &str, pairs: Vec<(Bytes, Bytes)>) -> crate::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(key)
+            .is_none_or(|entry| entry.is_expired(now))
+        {
+            state.insert(key.to_string(), Value::Hash(HashMap::new()), None);
+        }
+        let hash = match state.entries.get_mut(key) {
+            Some(entry) => entry.data.as_hash_mut()?,
+            None => return Ok(0),
+        };
+        Ok(pairs
+            .into_iter()
+            .map(|(field, value)| hash.insert(field, value))
+            .filter(Option::is_none)
+            .count() as u64)
+    }
+
+    // 获取哈希中指定字段的值，键或字段不存在时返回 None
+    pub(crate) fn hget(&self, key: &str, field: &[u8]) -> crate::Result<Option<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Ok(entry.data.as_hash()?.get(field).cloned()),
+            _ => Ok(None),
+        }
+    }
+
+    // 删除哈希中的字段，返回实际删除的字段数量；哈希被删空后删除该键
+    pub(crate) fn hdel(&self, key: &str, fields: &[Bytes]) -> crate::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let hash = match state.entries.get_mut(key) {
+            Some(entry) if !entry.is_expired(now) => entry.data.as_hash_mut()?,
+            _ => return Ok(0),
+        };
+        let removed = fields
+            .iter()
+            .filter(|field| hash.remove(*field).is_some())
+            .count() as u64;
+        if hash.is_empty() {
+            state.remove(key);
+        }
+        Ok(removed)
+    }
+
+    // 返回哈希中所有的字段和值，键不存在时返回空列表
+    pub(crate) fn hgetall(&self, key: &str) -> crate::Result<Vec<(Bytes, Bytes)>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Ok(entry
+                .data
+                .as_hash()?
+                .iter()
+                .map(|(field, value)| (field.clone(), value.clone()))
+                .collect()),
+            _ => Ok(vec![]),
+        }
+    }
+
+    // 向集合中添加成员，键不存在时创建集合，返回新增的成员数量
+    pub(crate) fn sadd(&self, key: &str, members: Vec<Bytes>) -> crate::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        if state
+            .entries
+            .get(key)
+            .is_none_or(|entry| entry.is_expired(now))
+        {
+            state.insert(key.to_string(), Value::Set(HashSet::new()), None);
+        }
+        let set = match state.entries.get_mut(key) {
+            Some(entry) => entry.data.as_set_mut()?,
+            None => return Ok(0),
+        };
+        Ok(members
+            .into_iter()
+            .map(|member| set.insert(member))
+            .filter(|&added| added)
+            .count() as u64)
+    }
+
+    // 从集合中移除成员，返回实际移除的成员数量；集合被删空后删除该键
+    pub(crate) fn srem(&self, key: &str, members: &[Bytes]) -> crate::Result<u64> {
+        let mut state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+
+        let set = match state.entries.get_mut(key) {
+            Some(entry) if !entry.is_expired(now) => entry.data.as_set_mut()?,
+            _ => return Ok(0),
+        };
+        let removed = members.iter().filter(|member| set.remove(*member)).count() as u64;
+        if set.is_empty() {
+            state.remove(key);
+        }
+        Ok(removed)
+    }
+
+    // 返回集合中的所有成员，键不存在时返回空列表
+    pub(crate) fn smembers(&self, key: &str) -> crate::Result<Vec<Bytes>> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => {
+                Ok(entry.data.as_set()?.iter().cloned().collect())
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    // 判断成员是否在集合中，键不存在时返回 false
+    pub(crate) fn sismember(&self, key: &str, member: &[u8]) -> crate::Result<bool> {
+        let state = self.shared.state.lock().unwrap();
+        let now = Instant::now();
+        match state.entries.get(key) {
+            Some(entry) if !entry.is_expired(now) => Ok(entry.data.as_set()?.contains(member)),
+            _ => Ok(false),
+        }
+    }
+
+    // 订阅指定键的发布订阅频道
+    pub(crate) fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
+        use std::collections::hash_map::Entry;
+
+        // 获取互斥锁，以访问状态
+        let mut state = self.global.channels.lock().unwrap();
+
+        // 检查键是否已经存在于 pub_sub 中
+        match state.pub_sub.entry(key) {
+            // 如果键已经存在，则返回订阅者
+            Entry::Occupied(e) => e.get().subscribe(),
+            // 如果键不存在，则创建一个新的发布订阅频道，并返回订阅者
+            Entry::Vacant(e) => {
+                let (tx, rx) = broadcast::channel(self.global.channel_capacity);
+                e.insert(tx);
+                rx
+            }
+        }
+    }
+
+    // 按 glob 模式订阅发布订阅频道
+    pub(crate) fn psubscribe(&self, pattern: String) -> broadcast::Receiver<(String, Bytes)> {
+        use std::collections::hash_map::Entry;
+
+        let mut state = self.global.channels.lock().unwrap();
+        match state.pattern_subs.entry(pattern) {
+            Entry::Occupied(e) => e.get().subscribe(),
+            Entry::Vacant(e) => {
+                let (tx, rx) = broadcast::channel(self.global.channel_capacity);
+                e.insert(tx);
+                rx
+            }
+        }
+    }
+
+    // 发布指定键的值到发布订阅频道，同时发送给模式与频道名匹配的订阅者
+    // 返回收到消息的订阅者数量；订阅者已全部断开的频道和模式会在此时被移除
+    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
+        // 获取互斥锁，以访问状态
+        let mut state = self.global.channels.lock().unwrap();
+        // 从 pub_sub 中获取指定键的发送者，并发送值
+        let exact = match state.pub_sub.get(key).map(|tx| tx.send(value.clone())) {
+            Some(Ok(num)) => num,
+            Some(Err(_)) => {
+                state.pub_sub.remove(key);
+                0
+            }
+            None => 0,
+        };
+        let mut matched = 0;
+        state.pattern_subs.retain(|pattern, tx| {
+            if !glob_match(pattern.as_bytes(), key.as_bytes()) {
+                return true;
+            }
+            match tx.send((key.to_string(), value.clone())) {
+                Ok(num) => {
+                    matched += num;
+                    true
+                }
+                Err(_) => false,
+            }
+        });
+        exact + matched
+    }
+
+    // 按服务器配置的限制推断值使用的编码方式
+    fn encoding(&self, value: &Value) -> &'static str {
+        value.encoding(
+            self.global.set_max_listpack_entries,
+            self.global.set_max_listpack_value,
+        )
+    }
+
+    // 获取指定键的值所使用的编码方式，键不存在时返回 None
+    pub(crate) fn object_encoding(&self, key: &str) -> Option<&'static str> {
+        let state = self.shared.state.lock().unwrap();
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .map(|entry| self.encoding(&entry.data))
+    }
+
+    // 获取指定键经过衰减后的 LFU 访问频率，键不存在时返回 None
+    pub(crate) fn object_freq(&self, key: &str) -> Option<u8> {
+        let state = self.shared.state.lock().unwrap();
+        state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .map(Entry::lfu_decayed)
+    }
+
+    // 按照 Redis 的规则推断字符串使用的编码方式：
+    // 不超过 20 字节且能解析为 i64 的使用 int，不超过 44 字节的使用 embstr，其余使用 raw
+    pub(crate) fn string_encoding(data: &Bytes) -> &'static str {
+        if data.len() <= 20 && string_as_int(data).is_some() {
+            "int"
+        } else if data.len() <= 44 {
+            "embstr"
+        } else {
+            "raw"
+        }
+    }
+
+    // 记录一条 ACL 违规，超过上限时丢弃最旧的记录
+    pub(crate) fn acl_log_push(&self, entry: AclLogEntry) {
+        let mut log = self.global.acl_log.lock().unwrap();
+        if log.len() == ACL_LOG_MAX_LEN {
+            log.pop_back();
+        }
+        log.push_front(entry);
+    }
+
+    // 返回最近的 ACL 违规记录，最新的在前
+    pub(crate) fn acl_log(&self, count: Option<usize>) -> Vec<AclLogEntry> {
+        let log = self.global.acl_log.lock().unwrap();
+        let count = count.unwrap_or(log.len());
+        log.iter().take(count).cloned().collect()
+    }
+
+    // 清空 ACL 日志
+    pub(crate) fn acl_log_reset(&self) {
+        self.global.acl_log.lock().unwrap().clear();
+    }
+
+    // 返回至少有一个订阅者且名称匹配模式的发布订阅频道，顺带清理已无订阅者的频道
+    pub(crate) fn pubsub_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        let mut state = self.global.channels.lock().unwrap();
+        state.pub_sub.retain(|_, tx| tx.receiver_count() > 0);
+        state
+            .pub_sub
+            .keys()
+            .filter(|channel| {
+                pattern.is_none_or(|pattern| glob_match(pattern.as_bytes(), channel.as_bytes()))
+            })
+            .cloned()
+            .collect()
+    }
+
+    // 返回每个指定频道的订阅者数量，不存在的频道计为 0
+    pub(crate) fn pubsub_numsub(&self, channels: &[String]) -> Vec<(String, u64)> {
+        let state = self.global.channels.lock().unwrap();
+        channels
+            .iter()
+            .map(|channel| {
+                let num = state
+                    .pub_sub
+                    .get(channel)
+                    .map_or(0, |tx| tx.receiver_count() as u64);
+                (channel.clone(), num)
+            })
+            .collect()
+    }
+
+    // 返回至少有一个订阅者的模式数量
+    pub(crate) fn pubsub_patterns(&self) -> usize {
+        let state = self.global.channels.lock().unwrap();
+        state
+            .pattern_subs
+            .values()
+            .filter(|tx| tx.receiver_count() > 0)
+            .count()
+    }
+
+    // 记录一个新建立的连接
+    pub(crate) fn client_connected(&self) {
+        self.global
+            .connected_clients
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 记录一个已关闭的连接
+    pub(crate) fn client_disconnected(&self) {
+        self.global
+            .connected_clients
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // 返回当前已建立的连接数
+    pub(crate) fn connected_clients(&self) -> usize {
+        self.global.connected_clients.load(Ordering::Relaxed)
+    }
+
+    // 返回服务器已运行的时间
+    pub(crate) fn uptime(&self) -> Duration {
+        self.global.started_at.elapsed()
+    }
+
+    // 是否配置了密码，配置后客户端需要先通过 `AUTH` 认证
+    pub(crate) fn requires_auth(&self) -> bool {
+        self.global.requirepass.is_some()
+    }
+
+    // 校验客户端提供的密码，未配置密码时总是返回 false
+    pub(crate) fn check_password(&self, password: &str) -> bool {
+        self.global.requirepass.as_ref().is_some_and(|requirepass| {
+            constant_time_eq(requirepass.as_bytes(), password.as_bytes())
+        })
+    }
+
+    // 记录一次命令调用
+    pub(crate) fn record_command(&self, name: &str) {
+        let mut stats = self.global.command_stats.lock().unwrap();
+        match stats.get_mut(name) {
+            Some(calls) => *calls += 1,
+            None => {
+                stats.insert(name.to_string(), 1);
+            }
+        }
+    }
+
+    // 返回每个命令调用次数的快照，按命令名排序
+    pub(crate) fn command_stats(&self) -> Vec<(String, u64)> {
+        let stats = self.global.command_stats.lock().unwrap();
+        let mut snapshot: Vec<_> = stats
+            .iter()
+            .map(|(name, calls)| (name.clone(), *calls))
+            .collect();
+        snapshot.sort_unstable();
+        snapshot
+    }
+}
+
+// LRU 时钟的最大值，与 Redis 一样使用 24 位
+const LRU_CLOCK_MAX: u64 = (1 << 24) - 1;
+
+// LFU 计数器的初始值，避免新写入的键立即被视为冷数据
+const LFU_INIT_VAL: u8 = 5;
+
+// LFU 计数器递增的对数因子，值越大计数器增长越慢
+const LFU_LOG_FACTOR: f64 = 10.0;
+
+// LFU 计数器的衰减周期
+const LFU_DECAY_TIME: Duration = Duration::from_secs(60);
+
+// 以对数概率递增 LFU 计数器，与 Redis 的 LFULogIncr 一致
+fn lfu_log_incr(counter: u8, rng: &mut u64) -> u8 {
+    if counter == u8::MAX {
+        return counter;
+    }
+    // xorshift64 伪随机数
+    *rng ^= *rng << 13;
+    *rng ^= *rng >> 7;
+    *rng ^= *rng << 17;
+    let r = *rng as f64 / u64::MAX as f64;
+    let baseval = counter.saturating_sub(LFU_INIT_VAL) as f64;
+    let p = 1.0 / (baseval * LFU_LOG_FACTOR + 1.0);
+    if r < p { counter + 1 } else { counter }
+}
+
+// 为伪随机数生成器生成一个非零种子
+fn lfu_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_nanos() as u64)
+        .unwrap_or(0)
+        | 1
+}
+
+// 将 [start, stop] 范围内可能为负数的索引转换为长度为 `len` 的列表中的实际位置
+// 越界的部分会被截断，范围为空时返回 None
+fn list_range(len: usize, start: i64, stop: i64) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        return None;
+    }
+    Some((start as usize, stop as usize))
+}
+
+// 将字符串解析为 i64，要求与整数的规范文本表示完全一致
+fn string_as_int(data: &Bytes) -> Option<i64> {
+    let value = std::str::from_utf8(data).ok()?.parse::<i64>().ok()?;
+    if value.to_string().as_bytes() == &data[..] {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+// 将字符串解析为有限的 f64，不接受前后空白、`nan` 和无穷大
+fn string_as_float(data: &Bytes) -> Option<f64> {
+    let value = std::str::from_utf8(data).ok()?.parse::<f64>().ok()?;
+    value.is_finite().then_some(value)
+}
+
+// 近似计算字符串值按简单 RDB 格式序列化后的长度：
+// 可以用 32 位整数表示的值按整数编码，其余为长度前缀加上数据本身
+fn rdb_string_len(data: &Bytes) -> u64 {
+    match string_as_int(data) {
+        Some(value) if i8::try_from(value).is_ok() => return 2,
+        Some(value) if i16::try_from(value).is_ok() => return 3,
+        Some(value) if i32::try_from(value).is_ok() => return 5,
+        _ => {}
+    }
+
+    let len = data.len() as u64;
+    rdb_len_prefix(len) + len
+}
+
+// RDB 格式中长度前缀占用的字节数
+fn rdb_len_prefix(len: u64) -> u64 {
+    if len < 1 << 6 {
+        1
+    } else if len < 1 << 14 {
+        2
+    } else {
+        5
+    }
+}
+
+// 比较两个字节串是否相等，耗时只与较长的一方有关，不会因为提前返回泄露相同前缀的长度
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let len = a.len().max(b.len());
+    let mut diff = u8::from(a.len() != b.len());
+    for i in 0..len {
+        diff |= a.get(i).copied().unwrap_or(0) ^ b.get(i).copied().unwrap_or(0);
+    }
+    diff == 0
+}
+
+// 为 Db 结构体实现 Drop 特征，用于在实例被销毁时执行清理操作
+impl Drop for Db {
+    fn drop(&mut self) {
+        // 最后一个 Db 实例被销毁时，关闭所有数据库的后台任务
+        if Arc::strong_count(&self.global) == 1 {
+            for shared in &self.global.databases {
+                // 设置状态为关闭
+                shared.state.lock().unwrap().shutdown = true;
+                // 通知后台任务
+                shared.background_task.notify_one();
+            }
+        }
+    }
+}
+
+// 定义一个异步函数，用于清除过期的任务
+async fn purge_expired_tasks(shared: Arc<Shared>) {
+    // 只要数据库未关闭，就一直循环执行清除任务
+    while !shared.is_shutdown() {
+        // 调用 `purge_expired_keys` 方法获取下一个过期时间
+        if let Some(when) = shared.purge_expired_keys() {
+            // 使用 `tokio::select!` 宏等待 `sleep_until` 或 `background_task` 通知
+            tokio::select! {
+                // 等待直到指定的时间 `when`
+                _=time::sleep_until(when)=>{}
+                // 或者等待 `background_task` 被通知
+                _=shared.background_task.notified()=>{}
+            }
+        } else {
+            // 如果没有找到过期时间，则等待 `background_task` 通知
+            shared.background_task.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::{DEFAULT_SET_MAX_LISTPACK_ENTRIES, DEFAULT_SET_MAX_LISTPACK_VALUE};
+
+    fn new_db() -> Db {
+        Db::new(
+            1,
+            16,
+            None,
+            DEFAULT_SET_MAX_LISTPACK_ENTRIES,
+            DEFAULT_SET_MAX_LISTPACK_VALUE,
+        )
+    }
+
+    // 键是否仍留在 entries 中，不论是否已过期
+    fn contains_entry(db: &Db, key: &str) -> bool {
+        db.shared.state.lock().unwrap().entries.contains_key(key)
+    }
+
+    // 写入一个带有 10 秒过期时间的键，再直接把过期时间改为当前时刻
+    // 清理任务仍按 10 秒后唤醒，键会以已过期但尚未清除的状态留在 entries 中
+    fn set_expired(db: &Db, key: &str) {
+        db.set(
+            key.to_string(),
+            Bytes::from("v"),
+            Some(Duration::from_secs(10)),
+            None,
+            false,
+        );
+        let mut state = db.shared.state.lock().unwrap();
+        state.entries.get_mut(key).unwrap().expires_at = Some(Instant::now());
+    }
+
+    #[tokio::test]
+    async fn set_switches_to_hashtable_past_listpack_entries() {
+        let db = new_db();
+        let members = (0..DEFAULT_SET_MAX_LISTPACK_ENTRIES)
+            .map(|i| Bytes::from(format!("m{}", i)))
+            .collect();
+        db.sadd("s", members).unwrap();
+        assert_eq!(db.object_encoding("s"), Some("listpack"));
+
+        db.sadd("s", vec![Bytes::from("extra")]).unwrap();
+        assert_eq!(db.object_encoding("s"), Some("hashtable"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sooner_expiration_wakes_purge_task() {
+        let db = new_db();
+        db.set(
+            "later".to_string(),
+            Bytes::from("v"),
+            Some(Duration::from_secs(10)),
+            None,
+            false,
+        );
+        // 让清理任务先按 10 秒后的过期时间进入睡眠
+        tokio::task::yield_now().await;
+        db.set(
+            "sooner".to_string(),
+            Bytes::from("v"),
+            Some(Duration::from_millis(50)),
+            None,
+            false,
+        );
+
+        time::sleep(Duration::from_millis(100)).await;
+        // 先释放锁再断言，避免断言失败时 `Db` 的析构函数遇到中毒的锁
+        let (sooner, later) = {
+            let state = db.shared.state.lock().unwrap();
+            (
+                state.entries.contains_key("sooner"),
+                state.entries.contains_key("later"),
+            )
+        };
+        assert!(!sooner);
+        assert!(later);
+    }
+
+    #[tokio::test]
+    async fn object_encoding_ignores_expired_key() {
+        let db = new_db();
+        set_expired(&db, "k");
+        assert_eq!(db.object_encoding("k"), None);
+    }
+
+    #[tokio::test]
+    async fn object_freq_ignores_expired_key() {
+        let db = new_db();
+        set_expired(&db, "k");
+        assert_eq!(db.object_freq("k"), None);
+    }
+
+    #[tokio::test]
+    async fn lrange_resolves_and_clamps_indices() {
+        let db = new_db();
+        let values = vec![Bytes::from("a"), Bytes::from("b"), Bytes::from("c")];
+        db.push("list", values, false).unwrap();
+
+        assert_eq!(db.lrange("list", 0, -1).unwrap(), ["a", "b", "c"]);
+        assert_eq!(db.lrange("list", -2, -1).unwrap(), ["b", "c"]);
+        assert!(db.lrange("list", 5, 2).unwrap().is_empty());
+        assert_eq!(db.lrange("list", 0, 1000).unwrap(), ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn msetnx_writes_nothing_when_any_key_exists() {
+        let db = new_db();
+        db.set("old".to_string(), Bytes::from("1"), None, None, false);
+
+        let pairs = vec![
+            ("old".to_string(), Bytes::from("2")),
+            ("new".to_string(), Bytes::from("3")),
+        ];
+        assert!(!db.msetnx(pairs));
+        assert_eq!(db.get("old", false).unwrap(), Some(Bytes::from("1")));
+        assert_eq!(db.get("new", false).unwrap(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn move_key_keeps_ttl_and_expires_in_target() {
+        let db = Db::new(
+            2,
+            16,
+            None,
+            DEFAULT_SET_MAX_LISTPACK_ENTRIES,
+            DEFAULT_SET_MAX_LISTPACK_VALUE,
+        );
+        let target = db.select(1).unwrap();
+        db.set(
+            "k".to_string(),
+            Bytes::from("v"),
+            Some(Duration::from_secs(10)),
+            None,
+            false,
+        );
+
+        assert!(db.move_key("k", 1).unwrap());
+        assert_eq!(db.ttl("k"), None);
+        assert_eq!(target.ttl("k"), Some(10));
+
+        // 由目标数据库的清理任务按原来的过期时间清除
+        time::sleep(Duration::from_secs(11)).await;
+        let contains = |db: &Db| db.shared.state.lock().unwrap().entries.contains_key("k");
+        assert!(!contains(&target));
+        assert!(!contains(&db));
+    }
+
+    #[tokio::test]
+    async fn publish_removes_channel_without_subscribers() {
+        let db = new_db();
+        let rx = db.subscribe("news".to_string());
+        drop(rx);
+
+        assert_eq!(db.publish("news", Bytes::from("hello")), 0);
+        let exists = db
+            .global
+            .channels
+            .lock()
+            .unwrap()
+            .pub_sub
+            .contains_key("news");
+        assert!(!exists);
+    }
+
+    #[tokio::test]
+    async fn exists_counts_duplicates_and_skips_expired() {
+        let db = new_db();
+        db.set("present".to_string(), Bytes::from("v"), None, None, false);
+        set_expired(&db, "expired");
+
+        let keys = ["present", "present", "absent", "expired"].map(String::from);
+        assert_eq!(db.exists(&keys), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn persist_soonest_key_survives_purge() {
+        let db = new_db();
+        for (key, secs) in [("soon", 1), ("later", 2)] {
+            db.set(
+                key.to_string(),
+                Bytes::from("v"),
+                Some(Duration::from_secs(secs)),
+                None,
+                false,
+            );
+        }
+        // 让清理任务先按最早的过期时间进入睡眠
+        tokio::task::yield_now().await;
+        assert!(db.persist("soon"));
+
+        time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(db.ttl("soon"), Some(-1));
+        assert!(!contains_entry(&db, "later"));
+    }
+
+    #[tokio::test]
+    async fn getset_interleaved_swaps_lose_no_values() {
+        let db = new_db();
+        db.set("k".to_string(), Bytes::from("init"), None, None, false);
+
+        // 多个线程交替执行 GETSET，每次换出的旧值都应该恰好被一个调用方拿到
+        let mut seen = std::thread::scope(|scope| {
+            let handles = (0..4)
+                .map(|t| {
+                    let db = db.clone();
+                    scope.spawn(move || {
+                        (0..100)
+                            .map(|i| {
+                                let value = Bytes::from(format!("{}-{}", t, i));
+                                db.getset("k".to_string(), value).unwrap().unwrap()
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        seen.push(db.get("k", false).unwrap().unwrap());
+
+        let mut expected = (0..4)
+            .flat_map(|t| (0..100).map(move |i| Bytes::from(format!("{}-{}", t, i))))
+            .collect::<Vec<_>>();
+        expected.push(Bytes::from("init"));
+        seen.sort();
+        expected.sort();
+        assert_eq!(seen, expected);
+    }
+
+    #[tokio::test]
+    async fn set_commands_add_remove_and_query_members() {
+        let db = new_db();
+        let members = vec![Bytes::from("a"), Bytes::from("b"), Bytes::from("a")];
+        assert_eq!(db.sadd("s", members).unwrap(), 2);
+        assert_eq!(db.sadd("s", vec![Bytes::from("b")]).unwrap(), 0);
+
+        let mut all = db.smembers("s").unwrap();
+        all.sort();
+        assert_eq!(all, ["a", "b"]);
+        assert!(db.sismember("s", b"a").unwrap());
+        assert!(!db.sismember("s", b"c").unwrap());
+
+        // 删除最后一个成员时键也被删除
+        assert_eq!(
+            db.srem("s", &[Bytes::from("a"), Bytes::from("c")]).unwrap(),
+            1
+        );
+        assert_eq!(db.srem("s", &[Bytes::from("b")]).unwrap(), 1);
+        assert_eq!(db.type_of("s"), None);
+        assert!(db.smembers("s").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_commands_reject_string_keys() {
+        let db = new_db();
+        db.set("str".to_string(), Bytes::from("v"), None, None, false);
+
+        let results = [
+            db.sadd("str", vec![Bytes::from("a")]).map(drop),
+            db.srem("str", &[Bytes::from("a")]).map(drop),
+            db.smembers("str").map(drop),
+            db.sismember("str", b"a").map(drop),
+        ];
+        for res in results {
+            assert_eq!(res.unwrap_err().to_string(), WRONGTYPE);
         }
     }
 }
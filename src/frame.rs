@@ -5,14 +5,26 @@ use std::io::Cursor;
 use std::num::TryFromIntError;
 use std::string::FromUtf8Error;
 
+/// 内联命令一行的最大长度，与 Redis 的 `PROTO_INLINE_MAX_SIZE` 一致
+const INLINE_MAX_LEN: usize = 64 * 1024;
+
+/// 单个字符串或数组声明长度的默认上限，与 Redis 的 `proto-max-bulk-len` 一致
+pub(crate) const DEFAULT_MAX_FRAME_SIZE: usize = 512 * 1024 * 1024;
+
 #[derive(Clone, Debug)]
 pub enum Frame {
     Simple(String),
     Error(String),
-    Integer(u64),
+    Integer(i64),
     Bulk(Bytes),
     Null,
     Array(Vec<Frame>),
+    /// 以下为 RESP3 类型，连接协商为 RESP2 时由 `Connection` 降级编码
+    Map(Vec<(Frame, Frame)>),
+    Set(Vec<Frame>),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(String),
 }
 
 #[derive(Debug)]
@@ -75,7 +87,7 @@ impl Frame {
         }
     }
 
-    pub(crate) fn push_int(&mut self, value: u64) {
+    pub(crate) fn push_int(&mut self, value: i64) {
         match self {
             Frame::Array(vec) => {
                 vec.push(Frame::Integer(value));
@@ -84,9 +96,27 @@ impl Frame {
         }
     }
 
+    pub(crate) fn push_frame(&mut self, frame: Frame) {
+        match self {
+            Frame::Array(vec) => {
+                vec.push(frame);
+            }
+            _ => panic!("not an array frame"),
+        }
+    }
+
+    /// 如果是批量字符串帧，则返回其内容；否则返回 `None`
+    pub(crate) fn as_bulk(&self) -> Option<&[u8]> {
+        match self {
+            Frame::Bulk(data) => Some(data),
+            _ => None,
+        }
+    }
+
     /// 检查 `Cursor<&[u8]>` 中的数据是否符合特定的协议格式
     /// 如果数据格式正确，则返回 `Ok(())`；如果数据格式不正确，则返回相应的错误
-    pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+    /// 字符串或数组声明的长度超过 `max_len` 时返回错误，避免客户端声明超大长度导致无限制地分配内存
+    pub fn check(src: &mut Cursor<&[u8]>, max_len: usize) -> Result<(), Error> {
         // 读取下一个字节，并根据字节值进行不同的处理
         match get_u8(src)? {
             // 如果是 '+'，则读取下一行数据
@@ -101,7 +131,7 @@ impl Frame {
             }
             // 如果是 ':', 则读取一个十进制数
             b':' => {
-                let _ = get_decimal(src)?;
+                let _ = get_signed_decimal(src)?;
                 Ok(())
             }
             // 如果是 '$'，则根据下一个字节的值进行不同的处理
@@ -111,20 +141,37 @@ impl Frame {
                     skip(src, 4)
                 // 如果下一个字节不是 '-'，则读取一个十进制数，并跳过相应数量的字节
                 } else {
-                    let len: usize = get_decimal(src)?.try_into()?;
+                    let len = get_length(src, max_len, "bulk")?;
                     skip(src, len + 2)
                 }
             }
-            // 如果是 '*'，则读取一个十进制数，并对每个值进行检查
-            b'*' => {
-                let len = get_decimal(src)?;
+            // 如果是 '*' 或 '~'，则读取一个十进制数，并对每个值进行检查
+            b'*' | b'~' => {
+                let len = get_length(src, max_len, "multibulk")?;
                 for _ in 0..len {
-                    Frame::check(src)?;
+                    Frame::check(src, max_len)?;
+                }
+                Ok(())
+            }
+            // 如果是 '%'，则读取键值对的数量，并对每个键和值进行检查
+            b'%' => {
+                let len = get_length(src, max_len, "multibulk")?;
+                for _ in 0..len * 2 {
+                    Frame::check(src, max_len)?;
                 }
                 Ok(())
             }
-            // 如果是其他字节，则返回错误
-            actual => Err(format!("protocol error;invalid frame type byte `{}`", actual).into()),
+            // 如果是 '_'、','、'#' 或 '('，则读取下一行数据
+            b'_' | b',' | b'#' | b'(' => {
+                get_line(src)?;
+                Ok(())
+            }
+            // 如果是其他字节，则按内联命令处理，读取一整行
+            _ => {
+                src.set_position(src.position() - 1);
+                get_inline_line(src)?;
+                Ok(())
+            }
         }
     }
 
@@ -145,10 +192,10 @@ impl Frame {
                 let string = String::from_utf8(line)?;
                 Ok(Frame::Error(string))
             }
-            // 如果是 ':', 则读取一个十进制数，并将其解析为一个整数帧
+            // 如果是 ':', 则读取一个有符号十进制数，并将其解析为一个整数帧
             b':' => {
-                let len = get_decimal(src)?;
-                Ok(Frame::Integer(len))
+                let value = get_signed_decimal(src)?;
+                Ok(Frame::Integer(value))
             }
             // 如果是 '$'，则根据下一个字节的值进行不同的处理
             b'$' => {
@@ -185,8 +232,72 @@ impl Frame {
                 }
                 Ok(Frame::Array(out))
             }
-            // 如果是其他字节，则返回错误
-            _ => unimplemented!(),
+            // 如果是 '~'，则按数组的方式解析为一个集合帧
+            b'~' => {
+                let len = get_decimal(src)?.try_into()?;
+                let mut out = Vec::with_capacity(len);
+                for _ in 0..len {
+                    out.push(Frame::parse(src)?);
+                }
+                Ok(Frame::Set(out))
+            }
+            // 如果是 '%'，则读取键值对的数量，并依次解析每个键和值
+            b'%' => {
+                let len = get_decimal(src)?.try_into()?;
+                let mut out = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = Frame::parse(src)?;
+                    let value = Frame::parse(src)?;
+                    out.push((key, value));
+                }
+                Ok(Frame::Map(out))
+            }
+            // 如果是 '_'，则解析为空帧
+            b'_' => {
+                if !get_line(src)?.is_empty() {
+                    return Err("protocol error; invalid null frame".into());
+                }
+                Ok(Frame::Null)
+            }
+            // 如果是 ','，则读取下一行数据，并将其解析为一个浮点数帧
+            b',' => {
+                let line = String::from_utf8(get_line(src)?.to_vec())?;
+                let value = line
+                    .parse::<f64>()
+                    .map_err(|_| "protocol error; invalid double")?;
+                Ok(Frame::Double(value))
+            }
+            // 如果是 '#'，则读取 `t` 或 `f`，并将其解析为一个布尔帧
+            b'#' => match get_line(src)? {
+                b"t" => Ok(Frame::Boolean(true)),
+                b"f" => Ok(Frame::Boolean(false)),
+                _ => Err("protocol error; invalid boolean".into()),
+            },
+            // 如果是 '('，则读取下一行数据，并将其解析为一个大数帧
+            b'(' => {
+                let line = String::from_utf8(get_line(src)?.to_vec())?;
+                Ok(Frame::BigNumber(line))
+            }
+            // 如果是其他字节，则按内联命令处理：读取一整行，按空白字符分割为批量帧组成的数组
+            _ => {
+                src.set_position(src.position() - 1);
+                let line = get_inline_line(src)?;
+                let parts = line
+                    .split(|b| b.is_ascii_whitespace())
+                    .filter(|part| !part.is_empty())
+                    .map(|part| Frame::Bulk(Bytes::copy_from_slice(part)))
+                    .collect();
+                Ok(Frame::Array(parts))
+            }
+        }
+    }
+
+    /// 将浮点数格式化为 Redis 使用的文本形式，`NaN` 和无穷大分别写作 `nan`、`inf`、`-inf`
+    pub(crate) fn format_double(value: f64) -> String {
+        if value.is_nan() {
+            "nan".to_string()
+        } else {
+            value.to_string()
         }
     }
 
@@ -222,16 +333,32 @@ impl fmt::Display for Frame {
             },
             // 如果是 Null 变体，输出 "(nil)"
             Frame::Null => "(nil)".fmt(f),
-            // 如果是 Array 变体，遍历数组中的每个元素，用空格分隔，然后格式化每个元素
-            Frame::Array(parts) => {
+            // 如果是 Array 或 Set 变体，遍历其中的每个元素，用空格分隔，然后格式化每个元素
+            Frame::Array(parts) | Frame::Set(parts) => {
                 for (i, part) in parts.iter().enumerate() {
                     if i > 0 {
                         write!(f, " ")?;
-                        part.fmt(f)?;
                     }
+                    part.fmt(f)?;
                 }
                 Ok(())
             }
+            // 如果是 Map 变体，依次格式化每个键和值，用空格分隔
+            Frame::Map(pairs) => {
+                for (i, (key, value)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{} {}", key, value)?;
+                }
+                Ok(())
+            }
+            // 如果是 Double 变体，按 Redis 的文本形式格式化浮点数
+            Frame::Double(value) => Frame::format_double(*value).fmt(f),
+            // 如果是 Boolean 变体，输出 "(true)" 或 "(false)"
+            Frame::Boolean(value) => write!(f, "({})", value),
+            // 如果是 BigNumber 变体，直接格式化数字字符串
+            Frame::BigNumber(value) => value.fmt(f),
         }
     }
 }
@@ -282,13 +409,47 @@ fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<u64, Error> {
     // 将读取到的字符串解析为 u64 类型的数字
     atoi::<u64>(line).ok_or_else(|| "protocol error; invalid decimal number".into())
 }
+/// 从 `Cursor<&[u8]>` 中读取下一行，并将其解析为一个 `i64` 类型的有符号十进制数
+/// 如果数据源中没有剩余字节或者解析的数字格式不正确，则返回 `Error`
+fn get_signed_decimal(src: &mut Cursor<&[u8]>) -> Result<i64, Error> {
+    use atoi::atoi;
+    // 读取下一行数据
+    let line = get_line(src)?;
+    // 将读取到的字符串解析为 i64 类型的数字
+    atoi::<i64>(line).ok_or_else(|| "protocol error; invalid decimal number".into())
+}
+
+/// 读取字符串或数组声明的长度，超过 `max_len` 时返回错误
+fn get_length(src: &mut Cursor<&[u8]>, max_len: usize, kind: &str) -> Result<usize, Error> {
+    let len = get_decimal(src)?;
+    match usize::try_from(len) {
+        Ok(len) if len <= max_len => Ok(len),
+        _ => Err(format!("protocol error; invalid {} length", kind).into()),
+    }
+}
+
+/// 从 `Cursor<&[u8]>` 中读取一行内联命令
+/// 行的长度超过 `INLINE_MAX_LEN` 时返回错误，避免客户端不发送行结束符导致缓冲区无限增长
+fn get_inline_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
+    match get_line(src) {
+        Ok(line) if line.len() > INLINE_MAX_LEN => {
+            Err("protocol error; too big inline request".into())
+        }
+        Err(Error::Incomplete) if src.remaining() > INLINE_MAX_LEN => {
+            Err("protocol error; too big inline request".into())
+        }
+        res => res,
+    }
+}
+
 /// 从 `Cursor<&[u8]>` 中读取下一行数据
 /// 如果数据源中没有剩余字节或者没有找到行结束符，则返回 `Error::Incomplete`
 fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
     // 获取当前光标位置作为行的起始位置
     let start = src.position() as usize;
-    // 获取数据源的长度作为行的结束位置
-    let end = src.get_ref().len() - 1;
+    // 行结束符占两个字节，最后一个可能的 '\r' 位于倒数第二个字节
+    // 缓冲区为空时使用 saturating_sub 避免下溢，此时循环不会执行，直接返回 `Incomplete`
+    let end = src.get_ref().len().saturating_sub(1);
 
     // 从当前位置开始遍历数据源，查找行结束符 '\r\n'
     for i in start..end {
@@ -303,3 +464,77 @@ fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
     // 如果没有找到行结束符，则返回 `Incomplete` 错误
     Err(Error::Incomplete)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_array_separates_elements_with_spaces() {
+        let frame = Frame::Array(vec![
+            Frame::Simple("a".to_string()),
+            Frame::Simple("b".to_string()),
+        ]);
+        assert_eq!(frame.to_string(), "a b");
+    }
+
+    #[test]
+    fn check_partial_frame_byte_by_byte() {
+        for input in [&b"+OK\r\n"[..], b"$5\r\nhello\r\n", b"*1\r\n:42\r\n"] {
+            // 帧完整之前的每个前缀都应该返回 `Incomplete`，包括空缓冲区
+            for len in 0..input.len() {
+                let mut cursor = Cursor::new(&input[..len]);
+                assert!(matches!(
+                    Frame::check(&mut cursor, DEFAULT_MAX_FRAME_SIZE),
+                    Err(Error::Incomplete)
+                ));
+            }
+            let mut cursor = Cursor::new(input);
+            assert!(Frame::check(&mut cursor, DEFAULT_MAX_FRAME_SIZE).is_ok());
+            assert_eq!(cursor.position() as usize, input.len());
+        }
+    }
+
+    #[test]
+    fn parse_bulk_frame() {
+        let input = b"$5\r\nhello\r\n";
+        let mut cursor = Cursor::new(&input[..]);
+        Frame::check(&mut cursor, DEFAULT_MAX_FRAME_SIZE).unwrap();
+
+        cursor.set_position(0);
+        let frame = Frame::parse(&mut cursor).unwrap();
+        assert!(matches!(frame, Frame::Bulk(data) if data == "hello"));
+        assert_eq!(cursor.position() as usize, input.len());
+    }
+
+    #[test]
+    fn check_rejects_oversized_bulk_length() {
+        let mut cursor = Cursor::new(&b"$999999999999\r\n"[..]);
+        match Frame::check(&mut cursor, DEFAULT_MAX_FRAME_SIZE) {
+            Err(Error::Other(err)) => {
+                assert_eq!(err.to_string(), "protocol error; invalid bulk length")
+            }
+            res => panic!("expected protocol error, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn parse_inline_command() {
+        let input = b"get foo\r\n";
+        let mut cursor = Cursor::new(&input[..]);
+        Frame::check(&mut cursor, DEFAULT_MAX_FRAME_SIZE).unwrap();
+
+        cursor.set_position(0);
+        match Frame::parse(&mut cursor).unwrap() {
+            Frame::Array(parts) => match &parts[..] {
+                [Frame::Bulk(name), Frame::Bulk(key)] => {
+                    assert_eq!(name, "get");
+                    assert_eq!(key, "foo");
+                }
+                parts => panic!("unexpected parts {:?}", parts),
+            },
+            frame => panic!("expected array, got {:?}", frame),
+        }
+        assert_eq!(cursor.position() as usize, input.len());
+    }
+}
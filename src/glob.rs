@@ -0,0 +1,95 @@
+/// 按照 Redis 的 glob 规则判断 `string` 是否与 `pattern` 匹配
+///
+/// 支持 `*`（任意长度的字符串）、`?`（任意单个字符）、`[...]`（字符集合，
+/// 可以使用 `^` 取反和 `a-z` 范围）以及用 `\` 转义特殊字符
+pub(crate) fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // 最近一次遇到的 `*` 在模式中的位置，以及它当前匹配到的字符串位置，用于回溯
+    let mut star: Option<(usize, usize)> = None;
+
+    while s < string.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    star = Some((p, s));
+                    p += 1;
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    s += 1;
+                    continue;
+                }
+                b'[' => {
+                    let (matched, len) = match_class(&pattern[p..], string[s]);
+                    if matched {
+                        p += len;
+                        s += 1;
+                        continue;
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == string[s] {
+                        p += 2;
+                        s += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == string[s] {
+                        p += 1;
+                        s += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // 当前位置不匹配时，让上一个 `*` 多匹配一个字符后重试
+        match star {
+            Some((star_p, star_s)) => {
+                p = star_p + 1;
+                s = star_s + 1;
+                star = Some((star_p, star_s + 1));
+            }
+            None => return false,
+        }
+    }
+
+    // 字符串已经用完，模式中剩余的部分只能全部是 `*`
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// 匹配以 `[` 开头的字符集合，返回是否匹配以及字符集合在模式中占用的长度
+fn match_class(pattern: &[u8], c: u8) -> (bool, usize) {
+    let mut i = 1;
+    let negate = pattern.get(i) == Some(&b'^');
+    if negate {
+        i += 1;
+    }
+
+    let mut matched = false;
+    while i < pattern.len() && pattern[i] != b']' {
+        if pattern[i] == b'\\' && i + 1 < pattern.len() {
+            matched |= pattern[i + 1] == c;
+            i += 2;
+        } else if i + 2 < pattern.len() && pattern[i + 1] == b'-' && pattern[i + 2] != b']' {
+            let (start, end) = if pattern[i] <= pattern[i + 2] {
+                (pattern[i], pattern[i + 2])
+            } else {
+                (pattern[i + 2], pattern[i])
+            };
+            matched |= start <= c && c <= end;
+            i += 3;
+        } else {
+            matched |= pattern[i] == c;
+            i += 1;
+        }
+    }
+
+    // 跳过结尾的 `]`，没有结尾时把模式的末尾视为字符集合的结束
+    if i < pattern.len() {
+        i += 1;
+    }
+    (matched != negate, i)
+}
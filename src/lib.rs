@@ -3,9 +3,12 @@ mod cmd;
 mod connection;
 mod db;
 mod frame;
+mod glob;
 mod parse;
+pub mod pool;
 pub mod server;
 mod shutdown;
+pub mod tls;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T> = std::result::Result<T, Error>;
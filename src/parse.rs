@@ -59,12 +59,50 @@ impl Parse {
         const MSG: &str = "protocol error; expected number";
         match self.next()? {
             Frame::Simple(data) => atoi::<u64>(data.as_bytes()).ok_or_else(|| MSG.into()),
-            Frame::Integer(v) => Ok(v),
+            Frame::Integer(v) => u64::try_from(v).map_err(|_| MSG.into()),
             Frame::Bulk(data) => atoi::<u64>(&data).ok_or_else(|| MSG.into()),
             frame => Err(format!("protocol error; expected int frame, got {:?}", frame).into()),
         }
     }
 
+    pub(crate) fn next_signed_int(&mut self) -> Result<i64, ParseError> {
+        use atoi::atoi;
+        const MSG: &str = "protocol error; expected number";
+        match self.next()? {
+            Frame::Simple(data) => atoi::<i64>(data.as_bytes()).ok_or_else(|| MSG.into()),
+            Frame::Integer(v) => Ok(v),
+            Frame::Bulk(data) => atoi::<i64>(&data).ok_or_else(|| MSG.into()),
+            frame => Err(format!("protocol error; expected int frame, got {:?}", frame).into()),
+        }
+    }
+
+    /// 读取一个浮点数参数，与 Redis 一致不接受 `nan`
+    pub(crate) fn next_float(&mut self) -> Result<f64, ParseError> {
+        const MSG: &str = "protocol error; expected float";
+        let value = match self.next()? {
+            Frame::Simple(data) => data.parse::<f64>().map_err(|_| MSG)?,
+            Frame::Integer(v) => v as f64,
+            Frame::Double(v) => v,
+            Frame::Bulk(data) => std::str::from_utf8(&data)
+                .ok()
+                .and_then(|data| data.parse::<f64>().ok())
+                .ok_or(MSG)?,
+            frame => {
+                return Err(
+                    format!("protocol error; expected float frame, got {:?}", frame).into(),
+                );
+            }
+        };
+        if value.is_nan() {
+            return Err(MSG.into());
+        }
+        Ok(value)
+    }
+
+    pub(crate) fn remaining(&mut self) -> Vec<Frame> {
+        self.parts.by_ref().collect()
+    }
+
     pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
         if self.parts.next().is_none() {
             Ok(())
@@ -0,0 +1,104 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+use crate::client::{self, Client};
+
+/// 客户端连接池，最多同时持有 `size` 个连接，连接在首次需要时才建立
+#[derive(Clone)]
+pub struct Pool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    addr: String,
+    // 空闲的连接，归还的连接放在末尾，优先复用最近使用过的连接
+    idle: Mutex<Vec<Client>>,
+    // 与服务器限制连接数的方式一致，每个借出的连接占用一个许可
+    // 借出时优先复用空闲连接，因此建立的连接总数不会超过许可数
+    limit_connections: Arc<Semaphore>,
+}
+
+/// 从连接池借出的连接，被销毁时归还给连接池，已失效的连接直接丢弃
+pub struct PooledClient {
+    client: Option<Client>,
+    shared: Arc<Shared>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Pool {
+    /// 创建连接到 `addr` 的连接池，先建立一个连接以确认地址可用
+    pub async fn connect(addr: &str, size: usize) -> crate::Result<Pool> {
+        if size == 0 {
+            return Err("pool size must be greater than 0".into());
+        }
+        let client = client::connect(addr).await?;
+        Ok(Pool {
+            shared: Arc::new(Shared {
+                addr: addr.to_string(),
+                idle: Mutex::new(vec![client]),
+                limit_connections: Arc::new(Semaphore::new(size)),
+            }),
+        })
+    }
+
+    /// 借出一个连接，所有连接都被借出时等待其中一个被归还
+    pub async fn get(&self) -> crate::Result<PooledClient> {
+        let permit = self
+            .shared
+            .limit_connections
+            .clone()
+            .acquire_owned()
+            .await?;
+
+        let idle = self.shared.idle.lock().unwrap().pop();
+        let client = match idle {
+            Some(client) => client,
+            None => {
+                debug!(addr = %self.shared.addr, "opening pooled connection");
+                client::connect(&self.shared.addr).await?
+            }
+        };
+
+        Ok(PooledClient {
+            client: Some(client),
+            shared: self.shared.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// 当前空闲的连接数量
+    pub fn idle(&self) -> usize {
+        self.shared.idle.lock().unwrap().len()
+    }
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            if client.is_broken() {
+                debug!("discarding broken pooled connection");
+                return;
+            }
+            self.shared.idle.lock().unwrap().push(client);
+        }
+    }
+}
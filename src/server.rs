@@ -1,19 +1,52 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    fmt, io,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::{
     net::{TcpListener, TcpStream},
     sync::{Semaphore, broadcast, mpsc},
     time,
 };
+use tokio_rustls::TlsAcceptor;
 use tracing::{debug, error, info};
 
-use crate::{cmd::Command, connection::Connection, db::Db, shutdown::Shutdown};
+use crate::{
+    cmd::Command,
+    connection::{BoxedSocket, Connection, is_disconnect},
+    db::{AclDenyReason, AclLogEntry, DEFAULT_USER, Db},
+    frame::Frame,
+    shutdown::Shutdown,
+};
+
+/// 服务器接受连接的监听套接字
+pub enum ServerListener {
+    Tcp(TcpListener),
+    /// 接受 TCP 连接后先完成 TLS 握手，再按 RESP 协议通信
+    Tls(TcpListener, TlsAcceptor),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// 已接受但可能尚未完成 TLS 握手的连接
+enum Accepted {
+    Plain(BoxedSocket),
+    Tls(TcpStream, TlsAcceptor),
+}
 
 #[derive(Debug)]
 struct Listener {
     db: Db,
-    listener: TcpListener,
+    listener: ServerListener,
     limit_connection: Arc<Semaphore>,
+    idle_timeout: Option<Duration>,
+    // 每个连接读缓冲区的初始容量
+    read_buffer: usize,
+    // 客户端发送的单个字符串或数组允许声明的最大长度
+    max_frame_size: usize,
     notify_shutdown: broadcast::Sender<()>,
     shutdown_complete_rx: mpsc::Receiver<()>,
     shutdown_complete_tx: mpsc::Sender<()>,
@@ -24,20 +57,97 @@ struct Handler {
     db: Db,
     connection: Connection,
     limit_connections: Arc<Semaphore>,
+    // 连接空闲超过该时间后关闭，`None` 表示不限制
+    idle_timeout: Option<Duration>,
     shutdown: Shutdown,
     _shutdown_complete: mpsc::Sender<()>,
 }
 
-const MAX_CONNECTIONS: usize = 256;
+/// 默认允许的最大并发连接数
+pub const DEFAULT_MAX_CONNECTIONS: usize = 256;
+
+/// 默认每个发布订阅频道可缓存的消息数
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
 
-pub async fn run(listener: TcpListener, shutdown: impl Future) -> crate::Result<()> {
+/// 默认每个连接读缓冲区的初始容量
+pub const DEFAULT_READ_BUFFER: usize = crate::connection::DEFAULT_BUFFER_CAPACITY;
+
+/// 默认客户端发送的单个字符串或数组允许声明的最大长度
+pub const DEFAULT_MAX_FRAME_SIZE: usize = crate::frame::DEFAULT_MAX_FRAME_SIZE;
+
+/// 默认的逻辑数据库数量，与 Redis 一致
+pub const DEFAULT_DATABASES: usize = 16;
+
+/// 默认集合使用 listpack 编码的最大成员数量，与 Redis 一致
+pub const DEFAULT_SET_MAX_LISTPACK_ENTRIES: usize = 128;
+
+/// 默认集合使用 listpack 编码时单个成员的最大字节数，与 Redis 一致
+pub const DEFAULT_SET_MAX_LISTPACK_VALUE: usize = 64;
+
+/// TLS 握手的最长时间，超时的连接直接关闭
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 服务器的运行参数
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// 最多同时处理的连接数，超出的连接会等待已有连接关闭
+    pub max_connections: usize,
+    /// 超过该时间没有收到命令的连接会被关闭，`None` 表示不限制
+    pub idle_timeout: Option<Duration>,
+    /// 每个发布订阅频道的缓冲容量，处理不及的订阅者会丢失超出的消息；为 0 时按 1 处理
+    pub channel_capacity: usize,
+    /// 每个连接读缓冲区的初始容量
+    pub read_buffer: usize,
+    /// 客户端发送的字符串或数组声明的长度超过该值时，连接以协议错误关闭
+    pub max_frame_size: usize,
+    /// 逻辑数据库的数量，客户端通过 `SELECT` 切换
+    pub databases: usize,
+    /// 设置后客户端需要先通过 `AUTH` 提供该密码才能执行其他命令
+    pub requirepass: Option<String>,
+    /// 集合成员数量超过该值时，`OBJECT ENCODING` 回复 hashtable 而不是 listpack
+    pub set_max_listpack_entries: usize,
+    /// 集合中有成员的字节数超过该值时，`OBJECT ENCODING` 回复 hashtable 而不是 listpack
+    pub set_max_listpack_value: usize,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            idle_timeout: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+            read_buffer: DEFAULT_READ_BUFFER,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            databases: DEFAULT_DATABASES,
+            requirepass: None,
+            set_max_listpack_entries: DEFAULT_SET_MAX_LISTPACK_ENTRIES,
+            set_max_listpack_value: DEFAULT_SET_MAX_LISTPACK_VALUE,
+        }
+    }
+}
+
+/// 按 `config` 运行服务器，直到 `shutdown` 完成
+pub async fn run(
+    listener: impl Into<ServerListener>,
+    config: Config,
+    shutdown: impl Future,
+) -> crate::Result<()> {
     let (notify_shutdown, _) = broadcast::channel(1);
     let (shutdown_complete_tx, shutdown_complete_rx) = mpsc::channel(1);
 
     let mut server = Listener {
-        listener,
-        db: Db::new(),
-        limit_connection: Arc::new(Semaphore::new(MAX_CONNECTIONS)),
+        listener: listener.into(),
+        db: Db::new(
+            config.databases,
+            config.channel_capacity,
+            config.requirepass,
+            config.set_max_listpack_entries,
+            config.set_max_listpack_value,
+        ),
+        limit_connection: Arc::new(Semaphore::new(config.max_connections)),
+        idle_timeout: config.idle_timeout,
+        read_buffer: config.read_buffer,
+        max_frame_size: config.max_frame_size,
         notify_shutdown,
         shutdown_complete_tx,
         shutdown_complete_rx,
@@ -73,28 +183,63 @@ impl Listener {
         loop {
             self.limit_connection.acquire().await?.forget();
 
-            let socket = self.accept().await?;
+            let (accepted, peer_addr) = self.accept().await?;
 
-            let mut handler = Handler {
-                db: self.db.clone(),
-                connection: Connection::new(socket),
-                limit_connections: self.limit_connection.clone(),
-                shutdown: Shutdown::new(self.notify_shutdown.subscribe()),
-                _shutdown_complete: self.shutdown_complete_tx.clone(),
-            };
+            let db = self.db.clone();
+            let limit_connections = self.limit_connection.clone();
+            let idle_timeout = self.idle_timeout;
+            let read_buffer = self.read_buffer;
+            let max_frame_size = self.max_frame_size;
+            let shutdown = Shutdown::new(self.notify_shutdown.subscribe());
+            let shutdown_complete = self.shutdown_complete_tx.clone();
             tokio::spawn(async move {
+                // TLS 握手在各自的任务中进行，避免握手缓慢的客户端阻塞接受新连接
+                let socket = match time::timeout(TLS_HANDSHAKE_TIMEOUT, accepted.establish()).await
+                {
+                    Ok(Ok(socket)) => socket,
+                    Ok(Err(err)) => {
+                        limit_connections.add_permits(1);
+                        debug!(cause=%err, "TLS handshake failed");
+                        return;
+                    }
+                    Err(_) => {
+                        limit_connections.add_permits(1);
+                        debug!("TLS handshake timed out");
+                        return;
+                    }
+                };
+                // 与信号量的许可一一对应，在 `Handler` 被销毁时减少
+                db.client_connected();
+
+                let mut connection = Connection::with_capacity(socket, read_buffer);
+                connection.set_max_frame_size(max_frame_size);
+                connection.peer_addr = peer_addr;
+
+                let mut handler = Handler {
+                    db,
+                    connection,
+                    limit_connections,
+                    idle_timeout,
+                    shutdown,
+                    _shutdown_complete: shutdown_complete,
+                };
                 if let Err(err) = handler.run().await {
-                    error!(cause=%err,"Connection error");
+                    // 对端中途断开连接很常见，不作为服务器的错误记录
+                    if is_disconnect(&err) {
+                        debug!(cause=%err, "Connection closed by peer");
+                    } else {
+                        error!(cause=%err,"Connection error");
+                    }
                 }
             });
         }
     }
 
-    async fn accept(&mut self) -> crate::Result<TcpStream> {
+    async fn accept(&mut self) -> crate::Result<(Accepted, String)> {
         let mut backoff = 1;
         loop {
             match self.listener.accept().await {
-                Ok((socket, _)) => return Ok(socket),
+                Ok(socket) => return Ok(socket),
                 Err(err) => {
                     if backoff > 64 {
                         return Err(err.into());
@@ -108,17 +253,96 @@ impl Listener {
     }
 }
 
+impl ServerListener {
+    // 接受一个连接，同时返回对端地址
+    async fn accept(&self) -> io::Result<(Accepted, String)> {
+        match self {
+            ServerListener::Tcp(listener) => {
+                let (socket, addr) = listener.accept().await?;
+                Ok((Accepted::Plain(Box::new(socket)), addr.to_string()))
+            }
+            ServerListener::Tls(listener, acceptor) => {
+                let (socket, addr) = listener.accept().await?;
+                Ok((Accepted::Tls(socket, acceptor.clone()), addr.to_string()))
+            }
+            // 对端通常没有绑定路径，与 Redis 一致使用监听的路径表示
+            #[cfg(unix)]
+            ServerListener::Unix(listener) => {
+                let (socket, _) = listener.accept().await?;
+                let path = listener.local_addr()?;
+                let addr = match path.as_pathname() {
+                    Some(path) => format!("{}:0", path.display()),
+                    None => String::new(),
+                };
+                Ok((Accepted::Plain(Box::new(socket)), addr))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for ServerListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerListener::Tcp(listener) => f.debug_tuple("Tcp").field(listener).finish(),
+            ServerListener::Tls(listener, _) => f.debug_tuple("Tls").field(listener).finish(),
+            #[cfg(unix)]
+            ServerListener::Unix(listener) => f.debug_tuple("Unix").field(listener).finish(),
+        }
+    }
+}
+
+impl Accepted {
+    async fn establish(self) -> io::Result<BoxedSocket> {
+        match self {
+            Accepted::Plain(socket) => Ok(socket),
+            Accepted::Tls(socket, acceptor) => Ok(Box::new(acceptor.accept(socket).await?)),
+        }
+    }
+}
+
+impl From<TcpListener> for ServerListener {
+    fn from(listener: TcpListener) -> ServerListener {
+        ServerListener::Tcp(listener)
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixListener> for ServerListener {
+    fn from(listener: UnixListener) -> ServerListener {
+        ServerListener::Unix(listener)
+    }
+}
+
 impl Handler {
     async fn run(&mut self) -> crate::Result<()> {
         while !self.shutdown.is_shutdown() {
+            let connection = &mut self.connection;
+            let idle_timeout = self.idle_timeout;
+            let read_frame = async move {
+                match idle_timeout {
+                    Some(idle_timeout) => time::timeout(idle_timeout, connection.read_frame())
+                        .await
+                        .ok(),
+                    None => Some(connection.read_frame().await),
+                }
+            };
+
             let maybe_frame = tokio::select! {
-                res = self.connection.read_frame()=>res?,
+                res = read_frame=>match res {
+                    Some(res)=>res?,
+                    None=>{
+                        info!("closing idle connection");
+                        return Ok(());
+                    }
+                },
                 _=self.shutdown.recv()=>{
                     return Ok(());
                 }
             };
 
             let frame = match maybe_frame {
+                // 与 Redis 一致，忽略空的内联命令和空数组
+                Some(Frame::Array(parts)) if parts.is_empty() => continue,
                 Some(frame) => frame,
                 None => return Ok(()),
             };
@@ -127,8 +351,31 @@ impl Handler {
 
             debug!(?cmd);
 
+            // 配置了密码时，未认证的连接只能执行认证相关的命令
+            if self.db.requires_auth()
+                && !self.connection.state.authenticated
+                && !cmd.allowed_without_auth()
+            {
+                self.db.acl_log_push(AclLogEntry {
+                    timestamp: SystemTime::now(),
+                    reason: AclDenyReason::Command,
+                    username: DEFAULT_USER.to_string(),
+                    client_addr: self.connection.peer_addr.clone(),
+                    call: cmd.get_name().to_string(),
+                });
+                let response = Frame::Error("NOAUTH Authentication required.".to_string());
+                debug!(?response);
+                self.connection.write_frame(&response).await?;
+                continue;
+            }
+
             cmd.apply(&self.db, &mut self.connection, &mut self.shutdown)
                 .await?;
+
+            // 客户端发送了 `QUIT`，回复已经发出，正常关闭连接
+            if self.connection.state.closing {
+                return Ok(());
+            }
         }
         Ok(())
     }
@@ -137,5 +384,6 @@ impl Handler {
 impl Drop for Handler {
     fn drop(&mut self) {
         self.limit_connections.add_permits(1);
+        self.db.client_disconnected();
     }
 }
@@ -0,0 +1,109 @@
+use std::{path::Path, sync::Arc};
+
+use tokio_rustls::{
+    TlsAcceptor, TlsConnector,
+    rustls::{
+        self, ClientConfig, DigitallySignedStruct, RootCertStore, ServerConfig, SignatureScheme,
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{self, CryptoProvider},
+        pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime, pem::PemObject},
+    },
+};
+
+/// 从 PEM 格式的证书链和私钥文件创建服务器使用的 `TlsAcceptor`
+pub fn acceptor(cert: &Path, key: &Path) -> crate::Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| format!("failed to load certificate {}: {}", cert.display(), err))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|err| format!("failed to load private key {}: {}", key.display(), err))?;
+
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// 创建客户端使用的 `TlsConnector`，使用 `ca` 文件中的证书校验服务器
+/// `insecure` 为 true 时不校验服务器证书，只应在测试环境中使用
+pub fn connector(ca: Option<&Path>, insecure: bool) -> crate::Result<TlsConnector> {
+    let config = if insecure {
+        ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification::new()))
+            .with_no_client_auth()
+    } else {
+        let ca = ca.ok_or("a CA certificate is required unless verification is disabled")?;
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(ca)
+            .map_err(|err| format!("failed to load CA certificate {}: {}", ca.display(), err))?
+        {
+            roots.add(cert?)?;
+        }
+        ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth()
+    };
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// 接受任何服务器证书，握手签名仍按正常方式校验
+#[derive(Debug)]
+struct NoVerification {
+    provider: CryptoProvider,
+}
+
+impl NoVerification {
+    fn new() -> NoVerification {
+        NoVerification {
+            provider: crypto::ring::default_provider(),
+        }
+    }
+}
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}